@@ -1,10 +1,18 @@
 use clap::{Parser, Subcommand};
 use env_logger::{Builder, Target};
-use log::{error, info};
+use log::{error, info, warn};
+use std::collections::HashMap;
+use std::env;
+use std::io::{self, IsTerminal, Write};
 use std::path::{Path, PathBuf};
-use std::{env, fs};
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
-use actionoscope::{Job, Workflow};
+use actionoscope::{
+    repository, schema, Access, Error, Job, JobResult, JobStatus, ParseOptions, PermissionsSource,
+    Step, StepContext, Workflow,
+};
 
 #[derive(Debug, Parser)]
 #[command(name = "actionoscope")]
@@ -15,15 +23,21 @@ use actionoscope::{Job, Workflow};
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+
+    /// Repository to read workflows from and run steps in; by default, the git
+    /// repository the current directory is in
+    #[arg(long, global = true, value_name = "PATH")]
+    repo: Option<PathBuf>,
 }
 
 #[derive(Debug, Subcommand)]
 enum Commands {
     /// Run a job or step
     Run {
-        /// Path to the workflow YAML file
+        /// Workflow to use: a path, a glob such as `ci-*.yml`, or the workflow's
+        /// `name:`; may be repeated
         #[arg(long, short = 'w')]
-        workflow_file: Option<String>,
+        workflow_file: Vec<String>,
 
         /// Job name to run
         #[arg(long, short = 'j')]
@@ -59,110 +73,220 @@ enum Commands {
             help = "Path to the .env file that serves as the secrets file"
         )]
         secrets_file: Option<String>,
+
+        /// Fail on keys GitHub does not accept instead of ignoring them
+        #[arg(long)]
+        strict: bool,
+
+        /// Value of a `workflow_dispatch` or `workflow_call` input, may be repeated
+        #[arg(long = "input", short = 'i', value_name = "NAME=VALUE", value_parser = parse_input)]
+        inputs: Vec<(String, String)>,
     },
     /// List workflow files
     Ls {
-        /// Path to the workflow YAML file
+        /// Workflow to use: a path, a glob such as `ci-*.yml`, or the workflow's
+        /// `name:`; may be repeated
         #[arg(long, short = 'w')]
-        workflow_file: Option<String>,
+        workflow_file: Vec<String>,
+
+        /// Fail on keys GitHub does not accept instead of ignoring them
+        #[arg(long)]
+        strict: bool,
     },
-}
+    /// Show the permissions the GITHUB_TOKEN of each job is granted
+    Permissions {
+        /// Workflow to use: a path, a glob such as `ci-*.yml`, or the workflow's
+        /// `name:`; may be repeated
+        #[arg(long, short = 'w')]
+        workflow_file: Vec<String>,
 
-fn validate_workflow_file(workflows_dir: &Path, workflow_file: &str) -> Option<PathBuf> {
-    let workflow_path = workflows_dir.join(workflow_file);
-    if workflow_path.exists() {
-        Some(workflow_path)
-    } else {
-        None
-    }
+        /// Only show this job
+        #[arg(long, short = 'j')]
+        job: Option<String>,
+
+        /// Fail on keys GitHub does not accept instead of ignoring them
+        #[arg(long)]
+        strict: bool,
+    },
+    /// Print the JSON Schema of workflow files, for editors and pre-commit hooks
+    Schema {
+        /// Print the schema of action metadata (action.yml) instead
+        #[arg(long)]
+        action: bool,
+    },
 }
 
-fn find_workflow_files(
-    workflow_file: Option<String>,
-) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
-    let current_dir = Path::new(".");
-    let workflows_dir = current_dir.join(".github").join("workflows");
-    let mut result = Vec::new();
-    if let Some(workflow_file) = workflow_file {
-        // check if the file exists in the current directory
-        let workflow_path = validate_workflow_file(current_dir, &workflow_file);
-        if let Some(path) = workflow_path {
-            result.push(path);
-            return Ok(result);
-        }
+/// The root of the repository `repo` (or the current directory) is in, or the
+/// directory itself outside of a git repository.
+fn repository_root(repo: Option<&Path>) -> PathBuf {
+    let start = repo.unwrap_or(Path::new("."));
+    repository::find_root(start).unwrap_or_else(|| start.to_path_buf())
+}
 
-        // fallback: check if the file exists in the .github/workflows directory
-        let exists = validate_workflow_file(&workflows_dir, &workflow_file);
-        if let Some(path) = exists {
-            result.push(path);
-            return Ok(result);
-        }
-        let err = format!("Provided workflow file {} was not found", workflow_file);
-        return Err(err.into());
-    }
-    for entry in fs::read_dir(workflows_dir)? {
-        let entry = entry?;
-        let path = entry.path();
-        if path.is_file() {
-            result.push(path);
-        }
-    }
+/// How a command failed.
+enum Failure {
+    Error(Error),
+    /// A failure that was logged when it happened, e.g. a step failing while
+    /// its job goes on; only its exit code is left to report.
+    Logged(u8),
+}
 
-    if !result.is_empty() {
-        Ok(result)
-    } else {
-        Err("No workflow files found in .github/workflows directory".into())
+impl From<Error> for Failure {
+    fn from(err: Error) -> Self {
+        Failure::Error(err)
     }
 }
 
+/// Runs the jobs in order. Unless `conditions` is off, a job only runs if its
+/// `if:` holds once the jobs it needs are done, so that jobs after a failure
+/// are skipped the way GitHub does. Returns the first failure.
 fn run_jobs(
-    jobs: Vec<&Job>,
+    workflow: &Workflow,
     job_names: Vec<String>,
+    conditions: bool,
     step: Option<String>,
     from_step: Option<String>,
     to_step: Option<String>,
-    env_vars: Option<std::collections::HashMap<String, String>>,
-    secret_vars: Option<std::collections::HashMap<String, String>>,
-) -> Result<(), Box<dyn std::error::Error>> {
-    for (index, job) in jobs.iter().enumerate() {
-        info!("Running job '{}'", job_names[index]);
-        if step.is_some() {
-            let step_name = &step.clone().unwrap();
-            let step = job.get_step(step_name).unwrap_or_else(|| {
-                error!("Step '{}' not found in the job '{:?}'", step_name, job);
-                std::process::exit(1);
-            });
-            step.run_cmd(env_vars.clone(), secret_vars.clone())?;
+    step_context: &dyn Fn(&Job) -> StepContext,
+) -> Result<(), Failure> {
+    let mut results: HashMap<String, JobResult> = HashMap::new();
+    let mut first_failure = None;
+    for name in &job_names {
+        let job = &workflow.jobs[name];
+        let mut context = step_context(job);
+        context.needs = workflow.needs_results(name, &results);
+        let met = if conditions {
+            workflow.job_condition_met(name, &results, &context)
         } else {
-            if from_step.is_some() && job.get_step(&from_step.clone().unwrap()).is_none() {
-                error!(
-                    "from-step '{}' not found in the job '{}'",
-                    from_step.clone().unwrap(),
-                    job_names[index]
-                );
-                std::process::exit(1);
+            Ok(true)
+        };
+        let outcome = met.and_then(|met| {
+            if !met {
+                log_skipped_job(workflow, name, &results);
+                return Ok(JobResult::Skipped);
             }
-            if to_step.is_some() && job.get_step(&to_step.clone().unwrap()).is_none() {
-                error!(
-                    "to-step '{}' not found in the job '{}'",
-                    to_step.clone().unwrap(),
-                    job_names[index]
-                );
-                std::process::exit(1);
+            info!("Running job '{name}'");
+            let step_not_found = |step: &str| Error::StepNotFound {
+                job: name.clone(),
+                step: step.to_string(),
+            };
+            if let Some(step_name) = &step {
+                let step = job
+                    .get_step(step_name)
+                    .ok_or_else(|| step_not_found(step_name))?;
+                match step.run_in(&context) {
+                    Err(err @ Error::Cancelled { .. }) => return Err(err),
+                    Err(err) if step.continues_on_error(&context) => {
+                        warn!("{err}, continuing as the step has `continue-on-error`");
+                    }
+                    Err(err) => {
+                        error!("{err}");
+                        return Err(err);
+                    }
+                    Ok(()) => {}
+                }
+            } else {
+                for bound in [&from_step, &to_step].into_iter().flatten() {
+                    if job.get_step(bound).is_none() {
+                        return Err(step_not_found(bound));
+                    }
+                }
+                run_steps(
+                    &job.get_all_steps_since(from_step.as_deref(), to_step.as_deref()),
+                    &mut context,
+                )?;
+            }
+            Ok(JobResult::Success)
+        });
+        let result = match outcome {
+            Ok(result) => result,
+            // an interrupt or a mistyped step stops the run right away
+            Err(err @ (Error::Cancelled { .. } | Error::StepNotFound { .. })) => {
+                return Err(err.into())
             }
-            for step in &job.get_all_steps_since(from_step.as_deref(), to_step.as_deref()) {
-                if let Err(e) = step.run_cmd(env_vars.clone(), secret_vars.clone()) {
-                    error!("Error running step '{}': {}", step.get_name_or_id(), e);
-                    std::process::exit(1);
+            Err(err) => {
+                if let Error::JobExpression { .. } = err {
+                    error!("{err}");
+                }
+                if job.continues_on_error(&context) {
+                    warn!("Job '{name}' failed, continuing as it has `continue-on-error`");
+                    JobResult::Success
+                } else {
+                    first_failure.get_or_insert(err);
+                    JobResult::Failure
                 }
             }
+        };
+        results.insert(name.clone(), result);
+    }
+    // failures of steps and jobs are logged as they happen
+    first_failure.map_or(Ok(()), |err| Err(Failure::Logged(exit_code(&err))))
+}
+
+/// Reports why the job `name` is skipped: its `if:` is false, or, without
+/// one, a job it needs did not succeed.
+fn log_skipped_job(workflow: &Workflow, name: &str, results: &HashMap<String, JobResult>) {
+    if let Some(condition) = &workflow.jobs[name].condition {
+        info!("Skipping job '{name}': its condition `{condition}` is false");
+        return;
+    }
+    let unsuccessful = workflow.job_ancestors(name).into_iter().find_map(|need| {
+        let result = results.get(need).copied().unwrap_or(JobResult::Skipped);
+        (result != JobResult::Success).then_some((need, result))
+    });
+    if let Some((need, result)) = unsuccessful {
+        let outcome = match result {
+            JobResult::Failure => "failed",
+            JobResult::Cancelled => "was cancelled",
+            _ => "was skipped",
+        };
+        info!("Skipping job '{name}' because job '{need}' {outcome}");
+    }
+}
+
+/// Runs the steps of a job the way a runner does: after a failure the job
+/// goes on, running only the steps whose `if:` still holds, e.g. `always()`
+/// or `failure()`. A step with `continue-on-error` may fail without that
+/// counting as a failure. Returns the first failure.
+fn run_steps(steps: &[&Step], context: &mut StepContext) -> Result<(), Error> {
+    let mut first_failure = None;
+    for step in steps {
+        let outcome = step.condition_met(context).and_then(|met| {
+            if met {
+                step.run_in(context)
+            } else {
+                match &step.condition {
+                    Some(condition) => info!(
+                        "Skipping step '{}': its condition `{condition}` is false",
+                        step.get_name_or_id()
+                    ),
+                    None => info!(
+                        "Skipping step '{}' because an earlier step failed",
+                        step.get_name_or_id()
+                    ),
+                }
+                Ok(())
+            }
+        });
+        match outcome {
+            Ok(()) => {}
+            // an interrupt stops the run right away
+            Err(err @ Error::Cancelled { .. }) => return Err(err),
+            Err(err) if step.continues_on_error(context) => {
+                warn!("{err}, continuing as the step has `continue-on-error`");
+            }
+            Err(err) => {
+                error!("{err}");
+                first_failure.get_or_insert(err);
+                context.status = JobStatus::Failure;
+            }
         }
     }
-    Ok(())
+    first_failure.map_or(Ok(()), Err)
 }
 
-fn ls_command(workflow_file: Option<String>) -> Result<(), Box<dyn std::error::Error>> {
-    let workflow_files = find_workflow_files(workflow_file.clone())?;
+fn ls_command(root: &Path, workflow_files: &[String], options: &ParseOptions) -> Result<(), Error> {
+    let workflow_files = repository::select_workflows(root, workflow_files)?;
 
     info!(
         "Found workflow file(s): {}",
@@ -174,40 +298,130 @@ fn ls_command(workflow_file: Option<String>) -> Result<(), Box<dyn std::error::E
     );
 
     for workflow_file in &workflow_files {
-        let workflow_content = fs::read_to_string(workflow_file.to_string_lossy().into_owned())
-            .unwrap_or_else(|err| {
-                error!("Failed to read the workflow file: {}", err);
-                std::process::exit(1);
-            });
-
-        let workflow = Workflow::from_yaml(&workflow_content).unwrap_or_else(|err| {
-            error!("Failed to parse the workflow file: {}", err);
-            std::process::exit(1);
-        });
+        let workflow = Workflow::from_file_with_options(workflow_file, options)?;
+        info!("Workflow '{}'", workflow.display_name());
         println!("{:#?}", workflow);
     }
     Ok(())
 }
 
+fn permissions_command(
+    root: &Path,
+    workflow_files: &[String],
+    job: Option<String>,
+    options: &ParseOptions,
+) -> Result<(), Error> {
+    for workflow_file in &repository::select_workflows(root, workflow_files)? {
+        let workflow = Workflow::from_file_with_options(workflow_file, options)?;
+        println!(
+            "Workflow '{}' ({})",
+            workflow.display_name(),
+            workflow_file.display()
+        );
+        let jobs: Vec<(&String, &Job)> = match &job {
+            Some(job_name) => workflow
+                .jobs
+                .get_key_value(job_name)
+                .map(|job| vec![job])
+                .ok_or_else(|| Error::JobNotFound {
+                    job: job_name.clone(),
+                })?,
+            None => workflow.jobs.iter().collect(),
+        };
+        for (name, job) in jobs {
+            let effective = workflow.effective_permissions(job);
+            let source = match effective.source {
+                PermissionsSource::Job => "set by the job",
+                PermissionsSource::Workflow => "inherited from the workflow",
+                PermissionsSource::RepositoryDefault => {
+                    "not set, the repository's default token permissions apply"
+                }
+            };
+            println!("  {name}: {source}");
+            if let Some(scopes) = effective.scopes() {
+                let granted: Vec<_> = scopes
+                    .iter()
+                    .filter(|(_, access)| **access != Access::None)
+                    .collect();
+                if granted.is_empty() {
+                    println!("    no access to any scope");
+                }
+                for (scope, access) in granted {
+                    println!("    {scope}: {access}");
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn parse_input(value: &str) -> Result<(String, String), String> {
+    value
+        .split_once('=')
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .ok_or_else(|| format!("expected NAME=VALUE, got `{value}`"))
+}
+
+/// Asks for the required inputs that were not given with `--input` and have no
+/// default, when someone is there to answer.
+fn prompt_for_inputs(workflow: &Workflow, provided: &mut HashMap<String, String>) {
+    if !io::stdin().is_terminal() {
+        return;
+    }
+    for (name, input) in workflow.on.inputs() {
+        if !input.required || input.default.is_some() || provided.contains_key(name) {
+            continue;
+        }
+        let mut hint = input.input_type.to_string();
+        if !input.options.is_empty() {
+            hint = format!("{hint}: {}", input.options.join(" | "));
+        }
+        loop {
+            match &input.description {
+                Some(description) => print!("{name} - {description} ({hint}): "),
+                None => print!("{name} ({hint}): "),
+            }
+            let _ = io::stdout().flush();
+            let mut line = String::new();
+            if io::stdin().read_line(&mut line).unwrap_or(0) == 0 {
+                return;
+            }
+            let value = line.trim_end_matches(['\r', '\n']);
+            match input.parse(value) {
+                Ok(_) => {
+                    provided.insert(name.to_string(), value.to_string());
+                    break;
+                }
+                Err(reason) => println!("Invalid value: {reason}"),
+            }
+        }
+    }
+}
+
 fn load_env_vars(env_file: Option<&str>) -> Option<std::collections::HashMap<String, String>> {
     if let Some(file) = env_file {
         dotenv::from_filename(file).ok()?;
         let env_vars: std::collections::HashMap<String, String> = env::vars().collect();
         Some(env_vars)
     } else {
-        return None;
+        None
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn run_command(
-    workflow_file: Option<String>,
+    root: &Path,
+    workflow_files: &[String],
     job: Option<String>,
     step: Option<String>,
     from_step: Option<String>,
     to_step: Option<String>,
     secrets_file: Option<String>,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let workflow_files = find_workflow_files(workflow_file.clone())?;
+    inputs: Vec<(String, String)>,
+    options: &ParseOptions,
+    cancelled: &Arc<AtomicBool>,
+) -> Result<(), Failure> {
+    let workflow_files = repository::select_workflows(root, workflow_files)?;
     let secrets = load_env_vars(secrets_file.as_deref());
 
     info!(
@@ -219,50 +433,91 @@ fn run_command(
             .join(", ")
     );
 
+    let github = repository::github_context(root);
     for workflow_file in &workflow_files {
-        let workflow_content = fs::read_to_string(workflow_file.to_string_lossy().into_owned())
-            .unwrap_or_else(|err| {
-                error!("Failed to read the workflow file: {}", err);
-                std::process::exit(1);
-            });
-
-        let workflow = Workflow::from_yaml(&workflow_content).unwrap_or_else(|err| {
-            error!("Failed to parse the workflow file: {}", err);
-            std::process::exit(1);
-        });
+        let workflow = Workflow::from_file_with_options(workflow_file, options)?;
+        info!("Running workflow '{}'", workflow.display_name());
+
+        let mut provided: HashMap<String, String> = inputs.iter().cloned().collect();
+        prompt_for_inputs(&workflow, &mut provided);
+        let inputs = workflow.resolve_inputs(&provided)?;
+        let step_context = |job: &Job| {
+            let mut context =
+                workflow.step_context_with_inputs(job, secrets.clone(), inputs.clone());
+            context.cancelled = cancelled.clone();
+            context.workspace = Some(root.to_path_buf());
+            context.github = github.clone();
+            context
+        };
 
         let mut jobs: Vec<&Job> = Vec::new();
         let mut job_names: Vec<String> = Vec::new();
-        if job.is_some() {
-            let job_name = &job.clone().unwrap();
-            let job = workflow.get_job(job_name).unwrap_or_else(|| {
-                error!("Job '{}' not found in the workflow", job_name);
-                std::process::exit(1);
-            });
+        if let Some(job_name) = &job {
+            let job = workflow
+                .get_job(job_name)
+                .ok_or_else(|| Error::JobNotFound {
+                    job: job_name.clone(),
+                })?;
             job_names.push(job_name.to_string());
             jobs.push(job);
         } else {
-            for (name, job) in &workflow.jobs {
+            for (name, job) in workflow.jobs_in_order()? {
                 job_names.push(name.clone());
                 jobs.push(job);
             }
         }
 
+        // reported up front, so that a failure caused by a missing service is
+        // not the first sign of it
+        for (name, job) in job_names.iter().zip(&jobs) {
+            let limitations = job.local_limitations();
+            if !limitations.is_empty() {
+                warn!("Job '{name}' cannot be fully reproduced locally:");
+            }
+            for limitation in limitations {
+                warn!("  - {limitation}");
+            }
+        }
+
         run_jobs(
-            jobs,
+            &workflow,
             job_names,
+            job.is_none(),
             step.clone(),
             from_step.clone(),
             to_step.clone(),
-            workflow.env.clone(),
-            secrets.clone(),
+            &step_context,
         )?;
     }
 
     Ok(())
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// The process exit code reported for each kind of failure; 2 is left to clap
+/// for usage errors.
+fn exit_code(err: &Error) -> u8 {
+    match err {
+        Error::Exit { .. } => 1,
+        Error::Parse(_)
+        | Error::InvalidWorkflow { .. }
+        | Error::InvalidEdit { .. }
+        | Error::Expression { .. }
+        | Error::JobExpression { .. } => 3,
+        Error::WorkflowNotFound { .. } | Error::ActionNotFound { .. } => 4,
+        Error::Io { .. } | Error::Wait { .. } => 5,
+        Error::JobNotFound { .. } => 6,
+        Error::StepNotFound { .. } => 7,
+        Error::MissingNeed { .. } | Error::NeedsCycle { .. } => 8,
+        Error::NothingToRun { .. } => 9,
+        Error::InvalidInput { .. } => 10,
+        Error::AmbiguousWorkflow { .. } => 11,
+        Error::Timeout { .. } => 124,
+        Error::Spawn { .. } => 127,
+        Error::Cancelled { .. } => 130,
+    }
+}
+
+fn main() -> ExitCode {
     if env::var("RUST_LOG").is_err() {
         Builder::new()
             .target(Target::Stdout)
@@ -274,7 +529,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let cli = Cli::parse();
 
-    match &cli.command {
+    let root = repository_root(cli.repo.as_deref());
+    let result = match &cli.command {
         Commands::Run {
             job,
             workflow_file,
@@ -282,15 +538,60 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             from_step,
             to_step,
             secrets_file,
-            ..
-        } => run_command(
-            workflow_file.clone(),
+            strict,
+            inputs,
+        } => {
+            let cancelled = Arc::new(AtomicBool::new(false));
+            let flag = cancelled.clone();
+            if let Err(err) = ctrlc::set_handler(move || flag.store(true, Ordering::SeqCst)) {
+                error!("Failed to install the Ctrl-C handler: {err}");
+            }
+            run_command(
+                &root,
+                workflow_file,
+                job.clone(),
+                step.clone(),
+                from_step.clone(),
+                to_step.clone(),
+                secrets_file.clone(),
+                inputs.clone(),
+                &ParseOptions { strict: *strict },
+                &cancelled,
+            )
+        }
+        Commands::Ls {
+            workflow_file,
+            strict,
+        } => ls_command(&root, workflow_file, &ParseOptions { strict: *strict })
+            .map_err(Failure::from),
+        Commands::Permissions {
+            workflow_file,
+            job,
+            strict,
+        } => permissions_command(
+            &root,
+            workflow_file,
             job.clone(),
-            step.clone(),
-            from_step.clone(),
-            to_step.clone(),
-            secrets_file.clone(),
-        ),
-        Commands::Ls { workflow_file } => ls_command(workflow_file.clone()),
+            &ParseOptions { strict: *strict },
+        )
+        .map_err(Failure::from),
+        Commands::Schema { action } => {
+            let schema = if *action {
+                schema::action()
+            } else {
+                schema::workflow()
+            };
+            println!("{schema:#}");
+            Ok(())
+        }
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(Failure::Error(err)) => {
+            error!("{err}");
+            ExitCode::from(exit_code(&err))
+        }
+        Err(Failure::Logged(code)) => ExitCode::from(code),
     }
 }
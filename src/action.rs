@@ -0,0 +1,295 @@
+//! Metadata of an action (`action.yml` or `action.yaml`): its inputs, outputs
+//! and how it runs.
+
+use crate::source::{Node, SourceMap};
+use crate::uses::is_workflow_path;
+use crate::{de, Error, ParseError, Result, Step, Uses};
+use indexmap::IndexMap;
+use schemars::gen::SchemaGenerator;
+use schemars::schema::Schema;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::path::{Path, PathBuf};
+
+/// File names an action's metadata is looked up under, in order.
+pub const METADATA_FILES: [&str; 2] = ["action.yml", "action.yaml"];
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Action {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inputs: Option<IndexMap<String, ActionInput>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outputs: Option<IndexMap<String, ActionOutput>>,
+    pub runs: Runs,
+    /// File the action was loaded from, if any.
+    #[serde(skip)]
+    pub path: Option<PathBuf>,
+}
+
+/// An input of an action. Actions receive every input as a string.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ActionInput {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub required: bool,
+    #[serde(
+        default,
+        deserialize_with = "de::scalar",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(with = "Option<de::Scalar>")]
+    pub default: Option<String>,
+    #[serde(rename = "deprecationMessage", skip_serializing_if = "Option::is_none")]
+    pub deprecation_message: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ActionOutput {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// The expression a composite action's output is read from, e.g.
+    /// `${{ steps.random.outputs.number }}`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+}
+
+/// `runs:` of an action, by what it is run with.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "RawRuns", into = "RawRuns")]
+pub enum Runs {
+    Composite {
+        steps: Vec<Step>,
+    },
+    /// A JavaScript action.
+    Node {
+        /// The runtime, e.g. `node20`.
+        using: String,
+        main: String,
+        pre: Option<String>,
+        pre_if: Option<String>,
+        post: Option<String>,
+        post_if: Option<String>,
+    },
+    Docker {
+        /// `Dockerfile`, a path to one, or a `docker://` image.
+        image: String,
+        args: Vec<String>,
+        env: Option<IndexMap<String, String>>,
+        entrypoint: Option<String>,
+        pre_entrypoint: Option<String>,
+        post_entrypoint: Option<String>,
+    },
+}
+
+impl Runs {
+    /// The value of `runs.using`.
+    pub fn using(&self) -> &str {
+        match self {
+            Runs::Composite { .. } => "composite",
+            Runs::Node { using, .. } => using,
+            Runs::Docker { .. } => "docker",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+struct RawRuns {
+    #[schemars(regex(pattern = r"^(composite|docker|node\d+)$"))]
+    using: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    steps: Option<Vec<Step>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    main: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pre: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pre_if: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    post: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    post_if: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    image: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    args: Vec<String>,
+    #[serde(
+        default,
+        deserialize_with = "de::scalar_map",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(with = "Option<de::ScalarMap>")]
+    env: Option<IndexMap<String, String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    entrypoint: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pre_entrypoint: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    post_entrypoint: Option<String>,
+}
+
+impl TryFrom<RawRuns> for Runs {
+    type Error = String;
+
+    fn try_from(raw: RawRuns) -> Result<Self, Self::Error> {
+        let missing = |key: &str| format!("`{key}` is required with `using: {}`", raw.using);
+        match raw.using.as_str() {
+            "composite" => Ok(Runs::Composite {
+                steps: raw.steps.ok_or_else(|| missing("steps"))?,
+            }),
+            "docker" => Ok(Runs::Docker {
+                image: raw.image.ok_or_else(|| missing("image"))?,
+                args: raw.args,
+                env: raw.env,
+                entrypoint: raw.entrypoint,
+                pre_entrypoint: raw.pre_entrypoint,
+                post_entrypoint: raw.post_entrypoint,
+            }),
+            using if is_node(using) => Ok(Runs::Node {
+                main: raw.main.ok_or_else(|| missing("main"))?,
+                using: raw.using,
+                pre: raw.pre,
+                pre_if: raw.pre_if,
+                post: raw.post,
+                post_if: raw.post_if,
+            }),
+            using => Err(format!(
+                "unsupported `using: {using}`: expected `composite`, `docker` or a Node.js runtime such as `node20`"
+            )),
+        }
+    }
+}
+
+fn is_node(using: &str) -> bool {
+    using
+        .strip_prefix("node")
+        .is_some_and(|version| !version.is_empty() && version.chars().all(|c| c.is_ascii_digit()))
+}
+
+impl From<Runs> for RawRuns {
+    fn from(runs: Runs) -> Self {
+        let mut raw = RawRuns {
+            using: runs.using().to_string(),
+            steps: None,
+            main: None,
+            pre: None,
+            pre_if: None,
+            post: None,
+            post_if: None,
+            image: None,
+            args: Vec::new(),
+            env: None,
+            entrypoint: None,
+            pre_entrypoint: None,
+            post_entrypoint: None,
+        };
+        match runs {
+            Runs::Composite { steps } => raw.steps = Some(steps),
+            Runs::Node {
+                main,
+                pre,
+                pre_if,
+                post,
+                post_if,
+                ..
+            } => {
+                raw.main = Some(main);
+                raw.pre = pre;
+                raw.pre_if = pre_if;
+                raw.post = post;
+                raw.post_if = post_if;
+            }
+            Runs::Docker {
+                image,
+                args,
+                env,
+                entrypoint,
+                pre_entrypoint,
+                post_entrypoint,
+            } => {
+                raw.image = Some(image);
+                raw.args = args;
+                raw.env = env;
+                raw.entrypoint = entrypoint;
+                raw.pre_entrypoint = pre_entrypoint;
+                raw.post_entrypoint = post_entrypoint;
+            }
+        }
+        raw
+    }
+}
+
+impl JsonSchema for Runs {
+    fn schema_name() -> String {
+        "Runs".to_string()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        // the keys `Runs::try_from` requires depend on `using`
+        let mut schema =
+            serde_json::to_value(RawRuns::json_schema(gen)).expect("schemas serialize to JSON");
+        schema["oneOf"] = json!([
+            { "properties": { "using": { "const": "composite" } }, "required": ["steps"] },
+            { "properties": { "using": { "const": "docker" } }, "required": ["image"] },
+            { "properties": { "using": { "pattern": r"^node\d+$" } }, "required": ["main"] },
+        ]);
+        de::schema(schema)
+    }
+}
+
+impl Action {
+    pub fn from_yaml(yaml_data: &str) -> Result<Self, ParseError> {
+        Self::parse(yaml_data, None)
+    }
+
+    /// Loads an action from its metadata file, or from the directory holding it.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let mut path = path.as_ref().to_path_buf();
+        if !path.is_file() {
+            path = METADATA_FILES
+                .iter()
+                .map(|file| path.join(file))
+                .find(|file| file.is_file())
+                .ok_or(Error::ActionNotFound { path })?;
+        }
+        let yaml_data = std::fs::read_to_string(&path).map_err(|source| Error::Io {
+            path: path.clone(),
+            source,
+        })?;
+        Ok(Self::parse(&yaml_data, Some(path))?)
+    }
+
+    fn parse(yaml_data: &str, path: Option<PathBuf>) -> Result<Self, ParseError> {
+        let root = Node::parse(yaml_data, path.clone())?;
+        let source_map = SourceMap::from_node(root.as_ref(), path.clone());
+        let mut action: Action = de::from_yaml(yaml_data, &source_map)?;
+        if let Runs::Composite { steps } = &mut action.runs {
+            for (index, step) in steps.iter_mut().enumerate() {
+                step.span = source_map.value(&format!("runs.steps[{index}]")).cloned();
+            }
+        }
+        action.path = path;
+        Ok(action)
+    }
+}
+
+impl Step {
+    /// The action this step `uses` when it is a local one (`uses: ./...`),
+    /// looked up from the root of the repository. Local reusable workflows are
+    /// not actions.
+    pub fn local_action(&self, repository_root: impl AsRef<Path>) -> Result<Option<Action>> {
+        match &self.uses {
+            Some(Uses::Local { path }) if !is_workflow_path(path) => {
+                Action::from_file(repository_root.as_ref().join(path)).map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,253 @@
+//! Builders to compose workflows in code, e.g. to generate similar workflows for
+//! many services.
+//!
+//! ```
+//! use actionoscope::{Job, Step, Trigger, Workflow};
+//!
+//! let workflow = Workflow::builder()
+//!     .name("CI")
+//!     .on(Trigger {
+//!         push: Some(Default::default()),
+//!         ..Default::default()
+//!     })
+//!     .job(
+//!         "build",
+//!         Job::builder("ubuntu-latest")
+//!             .step(Step::builder().uses("actions/checkout@v4".parse().unwrap()).build())
+//!             .step(Step::builder().name("Build").run("cargo build").build())
+//!             .build(),
+//!     )
+//!     .build()
+//!     .unwrap();
+//! assert!(workflow.to_yaml().starts_with("name: CI\non:\n  push: {}\n"));
+//! ```
+
+use crate::{
+    Container, ContinueOnError, Defaults, Job, Minutes, Permissions, Result, RunDefaults, RunsOn,
+    Step, Trigger, Uses, Workflow,
+};
+use indexmap::IndexMap;
+
+fn insert(map: &mut Option<IndexMap<String, String>>, name: String, value: String) {
+    map.get_or_insert_with(IndexMap::new).insert(name, value);
+}
+
+#[derive(Debug, Clone)]
+pub struct WorkflowBuilder {
+    workflow: Workflow,
+}
+
+impl Workflow {
+    pub fn builder() -> WorkflowBuilder {
+        WorkflowBuilder {
+            workflow: Workflow {
+                name: None,
+                on: Trigger::default(),
+                permissions: None,
+                env: None,
+                defaults: None,
+                jobs: IndexMap::new(),
+                path: None,
+                source_map: Default::default(),
+            },
+        }
+    }
+}
+
+impl WorkflowBuilder {
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.workflow.name = Some(name.into());
+        self
+    }
+
+    pub fn on(mut self, trigger: Trigger) -> Self {
+        self.workflow.on = trigger;
+        self
+    }
+
+    pub fn permissions(mut self, permissions: Permissions) -> Self {
+        self.workflow.permissions = Some(permissions);
+        self
+    }
+
+    pub fn env(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        insert(&mut self.workflow.env, name.into(), value.into());
+        self
+    }
+
+    pub fn run_defaults(mut self, run: RunDefaults) -> Self {
+        self.workflow.defaults = Some(Defaults { run: Some(run) });
+        self
+    }
+
+    /// Adds a job; jobs are written in the order they are added.
+    pub fn job(mut self, id: impl Into<String>, job: Job) -> Self {
+        self.workflow.jobs.insert(id.into(), job);
+        self
+    }
+
+    /// The workflow, if GitHub would accept it (see [`Workflow::validate`]).
+    pub fn build(self) -> Result<Workflow> {
+        self.workflow.validate()?;
+        Ok(self.workflow)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct JobBuilder {
+    job: Job,
+}
+
+impl Job {
+    pub fn builder(runs_on: impl Into<RunsOn>) -> JobBuilder {
+        JobBuilder {
+            job: Job {
+                needs: None,
+                condition: None,
+                runs_on: runs_on.into(),
+                permissions: None,
+                timeout_minutes: None,
+                continue_on_error: None,
+                container: None,
+                services: None,
+                env: None,
+                defaults: None,
+                steps: Vec::new(),
+                span: None,
+            },
+        }
+    }
+}
+
+impl JobBuilder {
+    pub fn needs(mut self, job: impl Into<String>) -> Self {
+        self.job.needs.get_or_insert_with(Vec::new).push(job.into());
+        self
+    }
+
+    pub fn condition(mut self, condition: impl Into<String>) -> Self {
+        self.job.condition = Some(condition.into());
+        self
+    }
+
+    pub fn permissions(mut self, permissions: Permissions) -> Self {
+        self.job.permissions = Some(permissions);
+        self
+    }
+
+    pub fn timeout_minutes(mut self, minutes: impl Into<Minutes>) -> Self {
+        self.job.timeout_minutes = Some(minutes.into());
+        self
+    }
+
+    pub fn continue_on_error(mut self, continue_on_error: impl Into<ContinueOnError>) -> Self {
+        self.job.continue_on_error = Some(continue_on_error.into());
+        self
+    }
+
+    pub fn container(mut self, container: Container) -> Self {
+        self.job.container = Some(container);
+        self
+    }
+
+    pub fn service(mut self, name: impl Into<String>, service: Container) -> Self {
+        self.job
+            .services
+            .get_or_insert_with(IndexMap::new)
+            .insert(name.into(), service);
+        self
+    }
+
+    pub fn env(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        insert(&mut self.job.env, name.into(), value.into());
+        self
+    }
+
+    pub fn run_defaults(mut self, run: RunDefaults) -> Self {
+        self.job.defaults = Some(Defaults { run: Some(run) });
+        self
+    }
+
+    pub fn step(mut self, step: Step) -> Self {
+        self.job.steps.push(step);
+        self
+    }
+
+    pub fn build(self) -> Job {
+        self.job
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct StepBuilder {
+    step: Step,
+}
+
+impl Step {
+    pub fn builder() -> StepBuilder {
+        StepBuilder::default()
+    }
+}
+
+impl StepBuilder {
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.step.name = Some(name.into());
+        self
+    }
+
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.step.id = Some(id.into());
+        self
+    }
+
+    /// Sets `if:`, the condition the step runs under.
+    pub fn condition(mut self, condition: impl Into<String>) -> Self {
+        self.step.condition = Some(condition.into());
+        self
+    }
+
+    pub fn uses(mut self, uses: Uses) -> Self {
+        self.step.uses = Some(uses);
+        self
+    }
+
+    /// Adds an input of the action the step `uses`.
+    pub fn with(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        insert(&mut self.step.with, name.into(), value.into());
+        self
+    }
+
+    pub fn env(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        insert(&mut self.step.env, name.into(), value.into());
+        self
+    }
+
+    pub fn timeout_minutes(mut self, minutes: impl Into<Minutes>) -> Self {
+        self.step.timeout_minutes = Some(minutes.into());
+        self
+    }
+
+    pub fn continue_on_error(mut self, continue_on_error: impl Into<ContinueOnError>) -> Self {
+        self.step.continue_on_error = Some(continue_on_error.into());
+        self
+    }
+
+    pub fn shell(mut self, shell: impl Into<String>) -> Self {
+        self.step.shell = Some(shell.into());
+        self
+    }
+
+    pub fn working_directory(mut self, working_directory: impl Into<String>) -> Self {
+        self.step.working_directory = Some(working_directory.into());
+        self
+    }
+
+    pub fn run(mut self, command: impl Into<String>) -> Self {
+        self.step.run = Some(command.into());
+        self
+    }
+
+    pub fn build(self) -> Step {
+        self.step
+    }
+}
@@ -0,0 +1,184 @@
+//! The `container` and `services` keys of a job: the Docker containers its
+//! steps run in and next to.
+
+use crate::Job;
+use indexmap::IndexMap;
+use schemars::gen::SchemaGenerator;
+use schemars::schema::Schema;
+use schemars::JsonSchema;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::json;
+use std::fmt;
+
+/// A container a job runs in, or a service container started next to it.
+///
+/// Accepts the image alone (`container: node:20`) or the full mapping.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(remote = "Self")]
+pub struct Container {
+    pub image: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub credentials: Option<Credentials>,
+    #[serde(
+        default,
+        deserialize_with = "crate::de::scalar_map",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(with = "Option<crate::de::ScalarMap>")]
+    pub env: Option<IndexMap<String, String>>,
+    /// Ports to expose, e.g. `5432:5432` or `80`.
+    #[serde(
+        default,
+        deserialize_with = "crate::de::scalars",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    #[schemars(with = "Vec<crate::de::Scalar>")]
+    pub ports: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub volumes: Vec<String>,
+    /// Extra `docker create` options.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub options: Option<String>,
+}
+
+/// Credentials to pull an image from a private registry.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct Credentials {
+    pub username: String,
+    pub password: String,
+}
+
+impl Container {
+    pub fn new(image: impl Into<String>) -> Self {
+        Container {
+            image: image.into(),
+            ..Default::default()
+        }
+    }
+
+    /// The keys set on this container that only apply inside Docker, and so
+    /// have no effect on a local run.
+    fn docker_only_keys(&self) -> Vec<&'static str> {
+        [
+            ("env", self.env.is_some()),
+            ("volumes", !self.volumes.is_empty()),
+            ("options", self.options.is_some()),
+            ("credentials", self.credentials.is_some()),
+        ]
+        .into_iter()
+        .filter_map(|(key, set)| set.then_some(key))
+        .collect()
+    }
+}
+
+impl<'de> Deserialize<'de> for Container {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match serde_yaml::Value::deserialize(deserializer)? {
+            serde_yaml::Value::String(image) => Ok(Container::new(image)),
+            value => Container::deserialize(value).map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+impl Serialize for Container {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if *self == Container::new(self.image.clone()) {
+            serializer.serialize_str(&self.image)
+        } else {
+            Container::serialize(self, serializer)
+        }
+    }
+}
+
+fn image_or_container(gen: &mut SchemaGenerator) -> serde_json::Value {
+    json!({ "oneOf": [{ "type": "string" }, gen.subschema_for::<Container>()] })
+}
+
+/// Schema of `container`, which may be just the image.
+pub(crate) fn container_schema(gen: &mut SchemaGenerator) -> Schema {
+    crate::de::schema(image_or_container(gen))
+}
+
+/// Schema of `services`, a map of service names to containers.
+pub(crate) fn services_schema(gen: &mut SchemaGenerator) -> Schema {
+    crate::de::schema(json!({
+        "type": "object",
+        "additionalProperties": image_or_container(gen),
+    }))
+}
+
+/// Part of a job a local run does not reproduce, and what happens instead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LocalLimitation {
+    /// The job's steps run in a container; locally they run on the host.
+    /// `ignored` lists the container keys that are dropped with it.
+    Container {
+        image: String,
+        ignored: Vec<&'static str>,
+    },
+    /// A service container is not started.
+    Service {
+        name: String,
+        image: String,
+        ports: Vec<String>,
+        ignored: Vec<&'static str>,
+    },
+}
+
+impl fmt::Display for LocalLimitation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let ignored = match self {
+            LocalLimitation::Container { image, ignored } => {
+                write!(
+                    f,
+                    "the job runs in the container `{image}`, its steps will run directly on this machine instead"
+                )?;
+                ignored
+            }
+            LocalLimitation::Service {
+                name,
+                image,
+                ports,
+                ignored,
+            } => {
+                write!(
+                    f,
+                    "the service `{name}` (`{image}`) will not be started, steps that use it need it running locally"
+                )?;
+                if !ports.is_empty() {
+                    write!(f, " (ports {})", ports.join(", "))?;
+                }
+                ignored
+            }
+        };
+        if !ignored.is_empty() {
+            let keys: Vec<String> = ignored.iter().map(|key| format!("`{key}`")).collect();
+            write!(f, "; ignored locally: {}", keys.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl Job {
+    /// What a local run of this job cannot reproduce, in the order declared.
+    pub fn local_limitations(&self) -> Vec<LocalLimitation> {
+        let container = self
+            .container
+            .iter()
+            .map(|container| LocalLimitation::Container {
+                image: container.image.clone(),
+                ignored: container.docker_only_keys(),
+            });
+        let services =
+            self.services
+                .iter()
+                .flatten()
+                .map(|(name, service)| LocalLimitation::Service {
+                    name: name.clone(),
+                    image: service.image.clone(),
+                    ports: service.ports.clone(),
+                    ignored: service.docker_only_keys(),
+                });
+        container.chain(services).collect()
+    }
+}
@@ -0,0 +1,193 @@
+//! Small (de)serialization helpers shared by the workflow model.
+
+use crate::source::{ParseError, SourceMap};
+use indexmap::IndexMap;
+use schemars::schema::Schema;
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serializer};
+
+/// A single string or a list of strings, the way GitHub accepts most filter keys
+/// (`types: opened` and `types: [opened]` mean the same).
+#[derive(Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub(crate) enum OneOrMany {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// Reads a [`OneOrMany`] as a list.
+pub(crate) fn one_or_many<'de, D>(deserializer: D) -> Result<Option<Vec<String>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(
+        Option::<OneOrMany>::deserialize(deserializer)?.map(|value| match value {
+            OneOrMany::One(value) => vec![value],
+            OneOrMany::Many(values) => values,
+        }),
+    )
+}
+
+/// Treats a key that is present but empty (`workflow_dispatch:`) as enabled with
+/// its default configuration, rather than as absent.
+pub(crate) fn present<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + Default,
+{
+    Ok(Some(
+        Option::<T>::deserialize(deserializer)?.unwrap_or_default(),
+    ))
+}
+
+/// A value of a map read by [`scalar_map`]; only used to describe it in schemas.
+#[derive(JsonSchema)]
+#[serde(untagged)]
+#[allow(dead_code)]
+pub(crate) enum Scalar {
+    String(String),
+    Number(f64),
+    Boolean(bool),
+}
+
+/// The schema of a map read by [`scalar_map`]; empty values are allowed.
+pub(crate) type ScalarMap = IndexMap<String, Option<Scalar>>;
+
+/// Reads a map of names to scalar values (`env:`, `with:`), keeping YAML numbers
+/// and booleans as the strings GitHub would pass along.
+pub(crate) fn scalar_map<'de, D>(
+    deserializer: D,
+) -> Result<Option<IndexMap<String, String>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let Some(map) = Option::<IndexMap<String, serde_yaml::Value>>::deserialize(deserializer)?
+    else {
+        return Ok(None);
+    };
+    map.into_iter()
+        .map(|(name, value)| {
+            let value = scalar_to_string(value).ok_or_else(|| {
+                serde::de::Error::custom(format!(
+                    "invalid value for `{name}`: expected a string, number or boolean"
+                ))
+            })?;
+            Ok((name, value))
+        })
+        .collect::<Result<_, _>>()
+        .map(Some)
+}
+
+/// Reads a single scalar the way [`scalar_map`] reads its values.
+pub(crate) fn scalar<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Option::<serde_yaml::Value>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(value) => scalar_to_string(value).map(Some).ok_or_else(|| {
+            serde::de::Error::custom("invalid value: expected a string, number or boolean")
+        }),
+    }
+}
+
+/// Reads a list of scalars, e.g. `ports: [80, "443:443"]`, as strings.
+pub(crate) fn scalars<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Vec::<serde_yaml::Value>::deserialize(deserializer)?
+        .into_iter()
+        .map(|value| {
+            scalar_to_string(value).ok_or_else(|| {
+                serde::de::Error::custom("invalid item: expected a string, number or boolean")
+            })
+        })
+        .collect()
+}
+
+fn scalar_to_string(value: serde_yaml::Value) -> Option<String> {
+    match value {
+        serde_yaml::Value::Null => Some(String::new()),
+        serde_yaml::Value::Bool(value) => Some(value.to_string()),
+        serde_yaml::Value::Number(value) => Some(value.to_string()),
+        serde_yaml::Value::String(value) => Some(value),
+        _ => None,
+    }
+}
+
+/// Writes a single item as a plain string, the way [`one_or_many`] reads it.
+pub(crate) fn serialize_one_or_many<S: Serializer>(
+    values: &Option<Vec<String>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match values.as_deref() {
+        Some([value]) => serializer.serialize_str(value),
+        Some(values) => serializer.collect_seq(values),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Deserializes a `T`, or a string holding a `${{ }}` expression that gives one
+/// at run time, e.g. `timeout-minutes: ${{ inputs.timeout }}`. The expression
+/// comes back as the error.
+pub(crate) fn or_expression<'de, D, T>(
+    deserializer: D,
+    expected: &str,
+) -> Result<Result<T, String>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw<T> {
+        Value(T),
+        String(String),
+    }
+    match Raw::<T>::deserialize(deserializer)? {
+        Raw::Value(value) => Ok(Ok(value)),
+        Raw::String(text) if text.contains("${{") => Ok(Err(text)),
+        Raw::String(text) => Err(serde::de::Error::invalid_type(
+            serde::de::Unexpected::Str(&text),
+            &expected,
+        )),
+    }
+}
+
+/// Writes whole numbers without a fractional part, as people write them
+/// (`replicas: 2` rather than `replicas: 2.0`).
+pub(crate) fn serialize_number<S: Serializer>(
+    value: &f64,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    if value.fract() == 0.0 && value.abs() < i64::MAX as f64 {
+        serializer.serialize_i64(*value as i64)
+    } else {
+        serializer.serialize_f64(*value)
+    }
+}
+
+/// A schema written as JSON, for types whose (de)serialization is hand-written.
+pub(crate) fn schema(value: serde_json::Value) -> Schema {
+    serde_json::from_value(value).expect("hand-written schemas are valid")
+}
+
+/// Deserializes a YAML document, reporting errors at the location of the value
+/// they are about.
+pub(crate) fn from_yaml<T: DeserializeOwned>(
+    yaml_data: &str,
+    source_map: &SourceMap,
+) -> Result<T, ParseError> {
+    let path = source_map.file().cloned();
+    // serde_yaml resolves anchors and aliases but leaves `<<` merge keys to
+    // the caller, so the document goes through a `Value` first
+    let mut value: serde_yaml::Value =
+        serde_yaml::from_str(yaml_data).map_err(|err| ParseError::from_yaml(err, path.clone()))?;
+    value
+        .apply_merge()
+        .map_err(|err| ParseError::from_yaml(err, path))?;
+    serde_path_to_error::deserialize(value)
+        .map_err(|err| ParseError::from_path_error(err, source_map))
+}
@@ -0,0 +1,410 @@
+//! Targeted edits of a workflow file that keep every byte they do not touch:
+//! comments, quoting, blank lines and key order survive, so automated fixes
+//! produce minimal diffs.
+
+use crate::source::{Node, NodeKind};
+use crate::{Error, ParseOptions, Result, Step, Uses, Workflow};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// A workflow file as written, along with the [`Workflow`] it parses to.
+#[derive(Debug, Clone)]
+pub struct Document {
+    source: String,
+    path: Option<PathBuf>,
+    workflow: Workflow,
+}
+
+/// Where [`Document::set_env`] sets a variable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnvTarget<'a> {
+    Workflow,
+    Job(&'a str),
+    /// A step, by job id and position in the job.
+    Step(&'a str, usize),
+}
+
+impl Document {
+    pub fn parse(yaml_data: &str) -> Result<Self> {
+        Self::load(yaml_data.to_string(), None)
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path).map_err(|source| Error::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::load(source, Some(path.to_path_buf()))
+    }
+
+    fn load(source: String, path: Option<PathBuf>) -> Result<Self> {
+        let workflow = Workflow::parse(&source, path.clone(), &ParseOptions::default())?;
+        Ok(Document {
+            source,
+            path,
+            workflow,
+        })
+    }
+
+    pub fn workflow(&self) -> &Workflow {
+        &self.workflow
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Writes the document to `path`.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        std::fs::write(path, &self.source).map_err(|source| Error::Io {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+
+    /// Replaces the command of a `run` step.
+    pub fn set_run(&mut self, job: &str, step: usize, run: &str) -> Result<()> {
+        let root = self.root()?;
+        let step_node = self.step_node(&root, job, step)?;
+        let Some((key, value)) = entry(step_node, "run") else {
+            return Err(invalid(format!(
+                "step {} of job '{job}' does not `run` a command",
+                step + 1
+            )));
+        };
+        let indent = key.start.column - 1 + self.indent_unit(&root);
+        let edit = self.replace_value(key, value, &render_scalar(run, indent));
+        self.apply(edit)
+    }
+
+    /// Points the `uses` of a step at another tag, branch or commit, keeping
+    /// the quoting and any trailing comment as they are.
+    pub fn set_uses_ref(&mut self, job: &str, step: usize, git_ref: &str) -> Result<()> {
+        let root = self.root()?;
+        let step_node = self.step_node(&root, job, step)?;
+        let current = entry(step_node, "uses").and_then(|(_, value)| {
+            let uses: Uses = value.as_str()?.parse().ok()?;
+            Some((value, uses.git_ref()?.to_string()))
+        });
+        let Some((value, current_ref)) = current else {
+            return Err(invalid(format!(
+                "step {} of job '{job}' does not use a remote action or workflow",
+                step + 1
+            )));
+        };
+        let raw = &self.source[value.start.offset..value.end.offset];
+        let at = raw.find('@').unwrap_or_default() + 1;
+        let start = value.start.offset + at;
+        let end = start + current_ref.len();
+        self.apply(Edit::replace(start..end, git_ref))
+    }
+
+    /// Sets an environment variable, replacing its value if it is already set
+    /// at that level and adding it (and the `env:` mapping if needed) otherwise.
+    pub fn set_env(&mut self, target: EnvTarget, name: &str, value: &str) -> Result<()> {
+        let root = self.root()?;
+        let unit = self.indent_unit(&root);
+        let owner = match target {
+            EnvTarget::Workflow => &root,
+            EnvTarget::Job(job) => self.job_node(&root, job)?,
+            EnvTarget::Step(job, step) => self.step_node(&root, job, step)?,
+        };
+        if is_flow(&self.source, owner) {
+            return Err(invalid("cannot add keys to a flow mapping".to_string()));
+        }
+        let owner_indent = first_key_column(owner).unwrap_or(1) - 1;
+
+        let edit = match entry(owner, "env") {
+            Some((_, env)) => {
+                if let Some((key, old)) = entry(env, name) {
+                    let indent = key.start.column - 1 + unit;
+                    self.replace_value(key, old, &render_scalar(value, indent))
+                } else if is_flow(&self.source, env) {
+                    let NodeKind::Mapping(entries) = &env.kind else {
+                        return Err(invalid("`env` is not a mapping".to_string()));
+                    };
+                    let rendered = render_scalar(value, 0);
+                    if rendered.contains('\n') {
+                        return Err(invalid(
+                            "cannot add a multi-line value to a flow mapping".to_string(),
+                        ));
+                    }
+                    match entries.last() {
+                        Some((_, last)) => {
+                            Edit::insert(last.end.offset, format!(", {name}:{rendered}"))
+                        }
+                        None => Edit::insert(env.end.offset - 1, format!("{name}:{rendered}")),
+                    }
+                } else {
+                    let indent = first_key_column(env).map_or(owner_indent + unit, |c| c - 1);
+                    let line = key_line(name, value, indent, unit);
+                    Edit::insert(self.line_after(content_end(&self.source, env)), line)
+                }
+            }
+            None => {
+                let block = format!(
+                    "{}env:\n{}",
+                    " ".repeat(owner_indent),
+                    key_line(name, value, owner_indent + unit, unit)
+                );
+                // `env` reads best before the jobs or steps it applies to
+                let before = match target {
+                    EnvTarget::Workflow => entry(owner, "jobs"),
+                    EnvTarget::Job(_) => entry(owner, "steps"),
+                    EnvTarget::Step(..) => None,
+                };
+                match before {
+                    Some((key, _)) => Edit::insert(self.line_start(key.start.offset), block),
+                    None => Edit::insert(self.line_after(content_end(&self.source, owner)), block),
+                }
+            }
+        };
+        self.apply(edit)
+    }
+
+    /// Inserts `step` at position `index` of the steps of `job`; `index` may be
+    /// the number of steps to append it.
+    pub fn insert_step(&mut self, job: &str, index: usize, step: &Step) -> Result<()> {
+        let root = self.root()?;
+        let job_node = self.job_node(&root, job)?;
+        let steps = match entry(job_node, "steps") {
+            Some((_, steps)) if !is_flow(&self.source, steps) => steps,
+            _ => {
+                return Err(invalid(format!(
+                    "job '{job}' has no block sequence of steps to insert into"
+                )))
+            }
+        };
+        let NodeKind::Sequence(items) = &steps.kind else {
+            return Err(invalid(format!("the steps of job '{job}' are not a list")));
+        };
+        if index > items.len() {
+            return Err(invalid(format!(
+                "job '{job}' has only {} step(s), cannot insert at {index}",
+                items.len()
+            )));
+        }
+        let Some(first) = items.first() else {
+            return Err(invalid(format!("job '{job}' has no steps to align with")));
+        };
+        // items are aligned on the `-` of the first one
+        let dash = self.source[..first.start.offset]
+            .rfind('-')
+            .unwrap_or_default();
+        let indent = dash - self.line_start(dash);
+        let item = render_item(step, indent)?;
+
+        let at = match items.get(index) {
+            Some(next) => {
+                let dash = self.source[..next.start.offset]
+                    .rfind('-')
+                    .unwrap_or_default();
+                self.leading_comments_start(self.line_start(dash))
+            }
+            None => self.line_after(content_end(&self.source, steps)),
+        };
+        self.apply(Edit::insert(at, item))
+    }
+
+    fn root(&self) -> Result<Node> {
+        Node::parse(&self.source, None)
+            .map_err(|err| invalid(err.message))?
+            .ok_or_else(|| invalid("the document is empty".to_string()))
+    }
+
+    fn job_node<'n>(&self, root: &'n Node, job: &str) -> Result<&'n Node> {
+        entry(root, "jobs")
+            .and_then(|(_, jobs)| entry(jobs, job))
+            .map(|(_, node)| node)
+            .ok_or_else(|| Error::JobNotFound {
+                job: job.to_string(),
+            })
+    }
+
+    fn step_node<'n>(&self, root: &'n Node, job: &str, step: usize) -> Result<&'n Node> {
+        let job_node = self.job_node(root, job)?;
+        let item = entry(job_node, "steps").and_then(|(_, steps)| match &steps.kind {
+            NodeKind::Sequence(items) => items.get(step),
+            _ => None,
+        });
+        item.ok_or_else(|| Error::StepNotFound {
+            job: job.to_string(),
+            step: format!("#{}", step + 1),
+        })
+    }
+
+    /// Indentation of the document, measured between `jobs:` and its first job.
+    fn indent_unit(&self, root: &Node) -> usize {
+        entry(root, "jobs")
+            .and_then(|(key, jobs)| first_key_column(jobs)?.checked_sub(key.start.column))
+            .filter(|unit| *unit > 0)
+            .unwrap_or(2)
+    }
+
+    /// Replaces everything between the colon after `key` and the end of `value`.
+    fn replace_value(&self, key: &Node, value: &Node, rendered: &str) -> Edit {
+        let colon = self.source[key.end.offset..]
+            .find(':')
+            .map_or(key.end.offset, |colon| key.end.offset + colon + 1);
+        Edit::replace(colon..value.end.offset.max(colon), rendered)
+    }
+
+    fn line_start(&self, offset: usize) -> usize {
+        self.source[..offset]
+            .rfind('\n')
+            .map_or(0, |newline| newline + 1)
+    }
+
+    /// The start of the line following the one `offset` is on.
+    fn line_after(&self, offset: usize) -> usize {
+        self.source[offset..]
+            .find('\n')
+            .map_or(self.source.len(), |newline| offset + newline + 1)
+    }
+
+    /// Moves `line_start` up over the comment lines right above it, which
+    /// describe what follows them.
+    fn leading_comments_start(&self, mut line_start: usize) -> usize {
+        while line_start > 0 {
+            let previous = self.line_start(line_start - 1);
+            if !self.source[previous..line_start]
+                .trim_start()
+                .starts_with('#')
+            {
+                break;
+            }
+            line_start = previous;
+        }
+        line_start
+    }
+
+    fn apply(&mut self, edit: Edit) -> Result<()> {
+        let mut source = self.source.clone();
+        let mut text = edit.text;
+        if edit.range.start == source.len() && !source.is_empty() && !source.ends_with('\n') {
+            text.insert(0, '\n');
+        }
+        source.replace_range(edit.range, &text);
+        let workflow = Workflow::parse(&source, self.path.clone(), &ParseOptions::default())
+            .map_err(|err| invalid(format!("the edit would make the workflow invalid: {err}")))?;
+        self.source = source;
+        self.workflow = workflow;
+        Ok(())
+    }
+}
+
+impl fmt::Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+struct Edit {
+    range: std::ops::Range<usize>,
+    text: String,
+}
+
+impl Edit {
+    fn replace(range: std::ops::Range<usize>, text: &str) -> Self {
+        Edit {
+            range,
+            text: text.to_string(),
+        }
+    }
+
+    fn insert(at: usize, text: String) -> Self {
+        Edit {
+            range: at..at,
+            text,
+        }
+    }
+}
+
+fn invalid(reason: String) -> Error {
+    Error::InvalidEdit { reason }
+}
+
+fn entry<'n>(node: &'n Node, name: &str) -> Option<(&'n Node, &'n Node)> {
+    match &node.kind {
+        NodeKind::Mapping(entries) => entries
+            .iter()
+            .find(|(key, _)| key.as_str() == Some(name))
+            .map(|(key, value)| (key, value)),
+        _ => None,
+    }
+}
+
+fn first_key_column(node: &Node) -> Option<usize> {
+    match &node.kind {
+        NodeKind::Mapping(entries) => entries.first().map(|(key, _)| key.start.column),
+        _ => None,
+    }
+}
+
+fn is_flow(source: &str, node: &Node) -> bool {
+    matches!(
+        source[node.start.offset..].chars().next(),
+        Some('{') | Some('[')
+    ) && !matches!(node.kind, NodeKind::Scalar(_))
+}
+
+/// Where the last value inside `node` ends; block collections otherwise end
+/// where the next token starts, possibly after comments and blank lines.
+fn content_end(source: &str, node: &Node) -> usize {
+    if is_flow(source, node) {
+        return node.end.offset;
+    }
+    let last = match &node.kind {
+        NodeKind::Scalar(_) => None,
+        NodeKind::Sequence(items) => items.last(),
+        NodeKind::Mapping(entries) => entries.last().map(|(_, value)| value),
+    };
+    last.map_or(node.end.offset, |last| content_end(source, last))
+}
+
+/// A scalar as it follows `key:`, quoted only if needed. Multi-line values are
+/// written as literal blocks indented by `indent` spaces.
+fn render_scalar(value: &str, indent: usize) -> String {
+    let yaml = serde_yaml::to_string(value).unwrap_or_default();
+    let yaml = yaml.strip_suffix('\n').unwrap_or(&yaml);
+    let mut lines = yaml.lines();
+    let mut rendered = format!(" {}", lines.next().unwrap_or_default());
+    for line in lines {
+        rendered.push('\n');
+        // serde_yaml indents block contents by two spaces
+        let line = line.strip_prefix("  ").unwrap_or(line);
+        if !line.is_empty() {
+            rendered.push_str(&" ".repeat(indent));
+            rendered.push_str(line);
+        }
+    }
+    rendered
+}
+
+fn key_line(name: &str, value: &str, indent: usize, unit: usize) -> String {
+    format!(
+        "{}{name}:{}\n",
+        " ".repeat(indent),
+        render_scalar(value, indent + unit)
+    )
+}
+
+/// `step` as a sequence item whose `-` is at column `indent`.
+fn render_item(step: &Step, indent: usize) -> Result<String> {
+    let yaml = serde_yaml::to_string(step)
+        .map_err(|err| invalid(format!("the step cannot be written: {err}")))?;
+    let mut item = String::new();
+    for (index, line) in yaml.lines().enumerate() {
+        if line.is_empty() {
+            item.push('\n');
+            continue;
+        }
+        item.push_str(&" ".repeat(indent));
+        item.push_str(if index == 0 { "- " } else { "  " });
+        item.push_str(line);
+        item.push('\n');
+    }
+    Ok(item)
+}
@@ -0,0 +1,235 @@
+//! Errors returned by the library.
+
+use crate::expression::ExpressionError;
+use crate::source::{Location, ParseError};
+use std::fmt;
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub enum Error {
+    /// No workflow file matched what was asked for.
+    WorkflowNotFound {
+        name: String,
+    },
+    /// A workflow `name:` given to select workflows is used by several files.
+    AmbiguousWorkflow {
+        name: String,
+        paths: Vec<PathBuf>,
+    },
+    /// A directory expected to hold an action has no `action.yml` or `action.yaml`.
+    ActionNotFound {
+        path: PathBuf,
+    },
+    /// A workflow file exists but could not be read.
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// A workflow file is not a valid workflow.
+    Parse(ParseError),
+    /// A workflow GitHub would reject although it has the expected structure.
+    InvalidWorkflow {
+        reason: String,
+    },
+    /// A [`Document`](crate::Document) edit that does not apply to the file.
+    InvalidEdit {
+        reason: String,
+    },
+    JobNotFound {
+        job: String,
+    },
+    StepNotFound {
+        job: String,
+        step: String,
+    },
+    /// A workflow input is missing, undeclared, or does not match its type.
+    InvalidInput {
+        name: String,
+        reason: String,
+    },
+    /// A job `needs` a job the workflow does not declare.
+    MissingNeed {
+        job: String,
+        need: String,
+    },
+    /// Jobs that transitively need themselves, first job repeated at the end.
+    NeedsCycle {
+        cycle: Vec<String>,
+    },
+    /// A step with neither `run` nor `uses`.
+    NothingToRun {
+        step: String,
+        location: Option<Location>,
+    },
+    /// A `${{ }}` expression of the step is not valid.
+    Expression {
+        step: String,
+        location: Option<Location>,
+        source: ExpressionError,
+    },
+    /// The `if:` of a job is not a valid expression.
+    JobExpression {
+        job: String,
+        source: ExpressionError,
+    },
+    /// The step's shell could not be started.
+    Spawn {
+        step: String,
+        location: Option<Location>,
+        shell: String,
+        source: std::io::Error,
+    },
+    /// The step's shell started, but waiting for it to finish failed.
+    Wait {
+        step: String,
+        location: Option<Location>,
+        source: std::io::Error,
+    },
+    /// The step ran and failed, either with an exit code or killed by a signal.
+    Exit {
+        step: String,
+        location: Option<Location>,
+        code: Option<i32>,
+        signal: Option<i32>,
+    },
+    /// The step was killed after running longer than its `timeout-minutes`.
+    Timeout {
+        step: String,
+        location: Option<Location>,
+        minutes: f64,
+    },
+    /// The step was killed because the run was cancelled.
+    Cancelled {
+        step: String,
+        location: Option<Location>,
+    },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+fn prefix(location: &Option<Location>) -> String {
+    location
+        .as_ref()
+        .map(|location| format!("{location}: "))
+        .unwrap_or_default()
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::WorkflowNotFound { name } => write!(f, "Workflow file {name} was not found"),
+            Error::AmbiguousWorkflow { name, paths } => {
+                let paths: Vec<String> = paths
+                    .iter()
+                    .map(|path| path.display().to_string())
+                    .collect();
+                write!(
+                    f,
+                    "Workflow name '{name}' is used by several files ({}); select one by its path",
+                    paths.join(", ")
+                )
+            }
+            Error::ActionNotFound { path } => write!(
+                f,
+                "No action.yml or action.yaml was found in {}",
+                path.display()
+            ),
+            Error::Io { path, source } => write!(f, "Failed to read {}: {source}", path.display()),
+            Error::Parse(err) => write!(f, "{err}"),
+            Error::InvalidWorkflow { reason } => write!(f, "Invalid workflow: {reason}"),
+            Error::InvalidEdit { reason } => write!(f, "Invalid edit: {reason}"),
+            Error::JobNotFound { job } => write!(f, "Job '{job}' not found in the workflow"),
+            Error::StepNotFound { job, step } => {
+                write!(f, "Step '{step}' not found in the job '{job}'")
+            }
+            Error::InvalidInput { name, reason } => write!(f, "Invalid input '{name}': {reason}"),
+            Error::MissingNeed { job, need } => write!(
+                f,
+                "Job '{job}' needs job '{need}', which does not exist in the workflow"
+            ),
+            Error::NeedsCycle { cycle } => {
+                write!(f, "Jobs form a `needs` cycle: {}", cycle.join(" -> "))
+            }
+            Error::NothingToRun { step, location } => write!(
+                f,
+                "{}No run command found for step id/name '{step}'",
+                prefix(location)
+            ),
+            Error::Expression {
+                step,
+                location,
+                source,
+            } => write!(
+                f,
+                "{}Step '{step}' has an invalid expression: {source}",
+                prefix(location)
+            ),
+            Error::JobExpression { job, source } => {
+                write!(f, "Job '{job}' has an invalid expression: {source}")
+            }
+            Error::Spawn {
+                step,
+                location,
+                shell,
+                source,
+            } => write!(
+                f,
+                "{}Step '{step}' could not start shell '{shell}': {source}",
+                prefix(location)
+            ),
+            Error::Wait {
+                step,
+                location,
+                source,
+            } => write!(
+                f,
+                "{}Step '{step}' could not be waited for: {source}",
+                prefix(location)
+            ),
+            Error::Exit {
+                step,
+                location,
+                code,
+                signal,
+            } => {
+                write!(f, "{}Step '{step}' failed", prefix(location))?;
+                match (code, signal) {
+                    (Some(code), _) => write!(f, " with exit code {code}"),
+                    (None, Some(signal)) => write!(f, " killed by signal {signal}"),
+                    (None, None) => Ok(()),
+                }
+            }
+            Error::Timeout {
+                step,
+                location,
+                minutes,
+            } => write!(
+                f,
+                "{}Step '{step}' timed out after {minutes} minute(s)",
+                prefix(location)
+            ),
+            Error::Cancelled { step, location } => {
+                write!(f, "{}Step '{step}' was cancelled", prefix(location))
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } | Error::Spawn { source, .. } | Error::Wait { source, .. } => {
+                Some(source)
+            }
+            Error::Parse(err) => Some(err),
+            Error::Expression { source, .. } | Error::JobExpression { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(err: ParseError) -> Self {
+        Error::Parse(err)
+    }
+}
@@ -0,0 +1,537 @@
+//! The `on:` section of a workflow: which events trigger it and how they are filtered.
+
+use crate::inputs::Input;
+use indexmap::IndexMap;
+use schemars::gen::SchemaGenerator;
+use schemars::schema::Schema;
+use schemars::JsonSchema;
+use serde::de::value::{MapAccessDeserializer, MapDeserializer, SeqAccessDeserializer};
+use serde::de::{self, Deserializer, SeqAccess, Visitor};
+use serde::{Deserialize, Serialize, Serializer};
+use serde_json::json;
+use std::fmt;
+
+/// Every event a workflow can be triggered by.
+///
+/// Accepts the three forms GitHub allows: a single event (`on: push`), a list of
+/// events (`on: [push, pull_request]`) and a map of events to their filters.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(remote = "Self", deny_unknown_fields)]
+pub struct Trigger {
+    #[serde(
+        default,
+        deserialize_with = "crate::de::present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub branch_protection_rule: Option<Activity>,
+    #[serde(
+        default,
+        deserialize_with = "crate::de::present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub check_run: Option<Activity>,
+    #[serde(
+        default,
+        deserialize_with = "crate::de::present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub check_suite: Option<Activity>,
+    #[serde(
+        default,
+        deserialize_with = "crate::de::present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub create: Option<Unfiltered>,
+    #[serde(
+        default,
+        deserialize_with = "crate::de::present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub delete: Option<Unfiltered>,
+    #[serde(
+        default,
+        deserialize_with = "crate::de::present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub deployment: Option<Unfiltered>,
+    #[serde(
+        default,
+        deserialize_with = "crate::de::present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub deployment_status: Option<Unfiltered>,
+    #[serde(
+        default,
+        deserialize_with = "crate::de::present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub discussion: Option<Activity>,
+    #[serde(
+        default,
+        deserialize_with = "crate::de::present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub discussion_comment: Option<Activity>,
+    #[serde(
+        default,
+        deserialize_with = "crate::de::present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub fork: Option<Unfiltered>,
+    #[serde(
+        default,
+        deserialize_with = "crate::de::present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub gollum: Option<Unfiltered>,
+    #[serde(
+        default,
+        deserialize_with = "crate::de::present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub issue_comment: Option<Activity>,
+    #[serde(
+        default,
+        deserialize_with = "crate::de::present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub issues: Option<Activity>,
+    #[serde(
+        default,
+        deserialize_with = "crate::de::present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub label: Option<Activity>,
+    #[serde(
+        default,
+        deserialize_with = "crate::de::present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub merge_group: Option<Activity>,
+    #[serde(
+        default,
+        deserialize_with = "crate::de::present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub milestone: Option<Activity>,
+    #[serde(
+        default,
+        deserialize_with = "crate::de::present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub page_build: Option<Unfiltered>,
+    #[serde(
+        default,
+        deserialize_with = "crate::de::present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub project: Option<Activity>,
+    #[serde(
+        default,
+        deserialize_with = "crate::de::present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub project_card: Option<Activity>,
+    #[serde(
+        default,
+        deserialize_with = "crate::de::present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub project_column: Option<Activity>,
+    #[serde(
+        default,
+        deserialize_with = "crate::de::present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub public: Option<Unfiltered>,
+    #[serde(
+        default,
+        deserialize_with = "crate::de::present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub pull_request: Option<PullRequest>,
+    #[serde(
+        default,
+        deserialize_with = "crate::de::present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub pull_request_review: Option<Activity>,
+    #[serde(
+        default,
+        deserialize_with = "crate::de::present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub pull_request_review_comment: Option<Activity>,
+    #[serde(
+        default,
+        deserialize_with = "crate::de::present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub pull_request_target: Option<PullRequest>,
+    #[serde(
+        default,
+        deserialize_with = "crate::de::present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub push: Option<Push>,
+    #[serde(
+        default,
+        deserialize_with = "crate::de::present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub registry_package: Option<Activity>,
+    #[serde(
+        default,
+        deserialize_with = "crate::de::present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub release: Option<Activity>,
+    #[serde(
+        default,
+        deserialize_with = "crate::de::present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub repository_dispatch: Option<Activity>,
+    #[serde(
+        default,
+        deserialize_with = "crate::de::present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub schedule: Option<Vec<Schedule>>,
+    #[serde(
+        default,
+        deserialize_with = "crate::de::present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub status: Option<Unfiltered>,
+    #[serde(
+        default,
+        deserialize_with = "crate::de::present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub watch: Option<Activity>,
+    #[serde(
+        default,
+        deserialize_with = "crate::de::present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub workflow_call: Option<WorkflowCall>,
+    #[serde(
+        default,
+        deserialize_with = "crate::de::present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub workflow_dispatch: Option<WorkflowDispatch>,
+    #[serde(
+        default,
+        deserialize_with = "crate::de::present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub workflow_run: Option<WorkflowRun>,
+    /// The events in the order the workflow lists them, when it was parsed.
+    #[serde(skip)]
+    #[schemars(skip)]
+    pub declared: Vec<String>,
+}
+
+impl Trigger {
+    /// Inputs declared by `workflow_dispatch` and `workflow_call`. An input both
+    /// declare is described by `workflow_dispatch`.
+    pub fn inputs(&self) -> IndexMap<&str, &Input> {
+        let dispatch = self
+            .workflow_dispatch
+            .as_ref()
+            .and_then(|dispatch| dispatch.inputs.as_ref());
+        let call = self
+            .workflow_call
+            .as_ref()
+            .and_then(|call| call.inputs.as_ref());
+        let mut inputs = IndexMap::new();
+        for (name, input) in dispatch.into_iter().chain(call).flatten() {
+            inputs.entry(name.as_str()).or_insert(input);
+        }
+        inputs
+    }
+
+    /// Names of the events this workflow reacts to, in the order the workflow
+    /// lists them. Events set in code come after those, in alphabetical order.
+    pub fn event_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = match serde_yaml::to_value(self) {
+            Ok(serde_yaml::Value::Mapping(events)) => events
+                .keys()
+                .filter_map(|key| key.as_str().map(str::to_string))
+                .collect(),
+            _ => Vec::new(),
+        };
+        // a stable sort keeps the alphabetical order of undeclared events
+        names.sort_by_key(|name| {
+            self.declared
+                .iter()
+                .position(|declared| declared == name)
+                .unwrap_or(usize::MAX)
+        });
+        names
+    }
+}
+
+/// Schema of the three forms [`Trigger`] accepts.
+pub(crate) fn trigger_schema(gen: &mut SchemaGenerator) -> Schema {
+    let events: Vec<String> = match Trigger::json_schema(gen) {
+        Schema::Object(schema) => schema
+            .object
+            .map(|object| object.properties.into_keys().collect())
+            .unwrap_or_default(),
+        Schema::Bool(_) => Vec::new(),
+    };
+    crate::de::schema(json!({
+        "oneOf": [
+            { "enum": events },
+            { "type": "array", "items": { "enum": events } },
+            gen.subschema_for::<Trigger>(),
+        ]
+    }))
+}
+
+impl Serialize for Trigger {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Trigger::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Trigger {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(TriggerVisitor)
+    }
+}
+
+struct TriggerVisitor;
+
+impl<'de> Visitor<'de> for TriggerVisitor {
+    type Value = Trigger;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an event name, a list of event names or a map of events")
+    }
+
+    fn visit_str<E: de::Error>(self, event: &str) -> Result<Trigger, E> {
+        let mut trigger = Trigger::deserialize(MapDeserializer::new(std::iter::once((event, ()))))?;
+        trigger.declared = vec![event.to_string()];
+        Ok(trigger)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<Trigger, A::Error> {
+        let events = Vec::<String>::deserialize(SeqAccessDeserializer::new(seq))?;
+        let mut trigger = Trigger::deserialize(MapDeserializer::new(
+            events.iter().map(|event| (event.as_str(), ())),
+        ))?;
+        trigger.declared = events;
+        Ok(trigger)
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> Result<Trigger, A::Error> {
+        let mut declared = Vec::new();
+        let mut trigger = Trigger::deserialize(MapAccessDeserializer::new(KeyRecorder {
+            map,
+            keys: &mut declared,
+        }))?;
+        trigger.declared = declared;
+        Ok(trigger)
+    }
+}
+
+/// Passes a map through, noting its keys on the way.
+struct KeyRecorder<'a, A> {
+    map: A,
+    keys: &'a mut Vec<String>,
+}
+
+impl<'de, A: de::MapAccess<'de>> de::MapAccess<'de> for KeyRecorder<'_, A> {
+    type Error = A::Error;
+
+    fn next_key_seed<K: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, A::Error> {
+        let Some(key) = self.map.next_key::<String>()? else {
+            return Ok(None);
+        };
+        self.keys.push(key.clone());
+        seed.deserialize(de::IntoDeserializer::into_deserializer(key))
+            .map(Some)
+    }
+
+    fn next_value_seed<V: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, A::Error> {
+        self.map.next_value_seed(seed)
+    }
+}
+
+/// Filters accepted by the `push` event.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct Push {
+    #[serde(
+        default,
+        deserialize_with = "crate::de::one_or_many",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(with = "Option<crate::de::OneOrMany>")]
+    pub branches: Option<Vec<String>>,
+    #[serde(
+        rename = "branches-ignore",
+        default,
+        deserialize_with = "crate::de::one_or_many",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(with = "Option<crate::de::OneOrMany>")]
+    pub branches_ignore: Option<Vec<String>>,
+    #[serde(
+        default,
+        deserialize_with = "crate::de::one_or_many",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(with = "Option<crate::de::OneOrMany>")]
+    pub tags: Option<Vec<String>>,
+    #[serde(
+        rename = "tags-ignore",
+        default,
+        deserialize_with = "crate::de::one_or_many",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(with = "Option<crate::de::OneOrMany>")]
+    pub tags_ignore: Option<Vec<String>>,
+    #[serde(
+        default,
+        deserialize_with = "crate::de::one_or_many",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(with = "Option<crate::de::OneOrMany>")]
+    pub paths: Option<Vec<String>>,
+    #[serde(
+        rename = "paths-ignore",
+        default,
+        deserialize_with = "crate::de::one_or_many",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(with = "Option<crate::de::OneOrMany>")]
+    pub paths_ignore: Option<Vec<String>>,
+}
+
+/// Filters accepted by the `pull_request` and `pull_request_target` events.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct PullRequest {
+    #[serde(
+        default,
+        deserialize_with = "crate::de::one_or_many",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(with = "Option<crate::de::OneOrMany>")]
+    pub types: Option<Vec<String>>,
+    #[serde(
+        default,
+        deserialize_with = "crate::de::one_or_many",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(with = "Option<crate::de::OneOrMany>")]
+    pub branches: Option<Vec<String>>,
+    #[serde(
+        rename = "branches-ignore",
+        default,
+        deserialize_with = "crate::de::one_or_many",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(with = "Option<crate::de::OneOrMany>")]
+    pub branches_ignore: Option<Vec<String>>,
+    #[serde(
+        default,
+        deserialize_with = "crate::de::one_or_many",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(with = "Option<crate::de::OneOrMany>")]
+    pub paths: Option<Vec<String>>,
+    #[serde(
+        rename = "paths-ignore",
+        default,
+        deserialize_with = "crate::de::one_or_many",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(with = "Option<crate::de::OneOrMany>")]
+    pub paths_ignore: Option<Vec<String>>,
+}
+
+/// Filters accepted by the `workflow_run` event.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct WorkflowRun {
+    #[serde(
+        default,
+        deserialize_with = "crate::de::one_or_many",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(with = "Option<crate::de::OneOrMany>")]
+    pub workflows: Option<Vec<String>>,
+    #[serde(
+        default,
+        deserialize_with = "crate::de::one_or_many",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(with = "Option<crate::de::OneOrMany>")]
+    pub types: Option<Vec<String>>,
+    #[serde(
+        default,
+        deserialize_with = "crate::de::one_or_many",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(with = "Option<crate::de::OneOrMany>")]
+    pub branches: Option<Vec<String>>,
+    #[serde(
+        rename = "branches-ignore",
+        default,
+        deserialize_with = "crate::de::one_or_many",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(with = "Option<crate::de::OneOrMany>")]
+    pub branches_ignore: Option<Vec<String>>,
+}
+
+/// Configuration of the `workflow_dispatch` event.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct WorkflowDispatch {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inputs: Option<IndexMap<String, Input>>,
+}
+
+/// Configuration of the `workflow_call` event.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct WorkflowCall {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inputs: Option<IndexMap<String, Input>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "Option<IndexMap<String, serde_json::Value>>")]
+    pub outputs: Option<serde_yaml::Mapping>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "Option<IndexMap<String, serde_json::Value>>")]
+    pub secrets: Option<serde_yaml::Mapping>,
+}
+
+/// A single `schedule` entry.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Schedule {
+    pub cron: String,
+}
+
+/// Events that can only be narrowed down by activity type.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct Activity {
+    #[serde(
+        default,
+        deserialize_with = "crate::de::one_or_many",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(with = "Option<crate::de::OneOrMany>")]
+    pub types: Option<Vec<String>>,
+}
+
+/// Events that take no configuration at all.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct Unfiltered {}
@@ -0,0 +1,903 @@
+//! `${{ }}` expressions: parsing, and evaluation against contexts such as `env`,
+//! `secrets` or `github`, with GitHub's loose typing rules and its functions.
+//!
+//! ```
+//! use actionoscope::expression::{self, Contexts, Value};
+//!
+//! let mut contexts = Contexts::new();
+//! contexts.insert("env", Value::object([("REGION", "eu-west-1")]));
+//! let value = expression::evaluate("env.REGION || 'us-east-1'", &contexts).unwrap();
+//! assert_eq!(value, Value::from("eu-west-1"));
+//! assert_eq!(
+//!     expression::interpolate("deploy to ${{ env.region }}", &contexts).unwrap(),
+//!     "deploy to eu-west-1"
+//! );
+//! ```
+
+mod functions;
+mod hash_files;
+
+use functions::Function;
+pub use hash_files::hash_files;
+use indexmap::IndexMap;
+use log::warn;
+use std::cmp::Ordering;
+use std::fmt;
+
+/// Contexts an expression may read whether or not they are provided; reading
+/// one that is not provided gives `null`.
+pub const CONTEXT_NAMES: [&str; 12] = [
+    "github", "env", "vars", "job", "jobs", "steps", "runner", "secrets", "strategy", "matrix",
+    "needs", "inputs",
+];
+
+/// A value an expression reads or produces.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum Value {
+    #[default]
+    Null,
+    Boolean(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    Object(IndexMap<String, Value>),
+}
+
+impl Value {
+    pub fn object<K: Into<String>, V: Into<Value>>(
+        entries: impl IntoIterator<Item = (K, V)>,
+    ) -> Self {
+        Value::Object(
+            entries
+                .into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+        )
+    }
+
+    /// Whether the value counts as true: everything but `false`, `0`, `NaN`,
+    /// `''` and `null`.
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Value::Null => false,
+            Value::Boolean(value) => *value,
+            Value::Number(value) => *value != 0.0 && !value.is_nan(),
+            Value::String(value) => !value.is_empty(),
+            Value::Array(_) | Value::Object(_) => true,
+        }
+    }
+
+    /// The value as a number, which is how values of different types are
+    /// compared: `null` is 0, booleans 1 and 0, and strings are parsed (an
+    /// empty one is 0, an invalid one `NaN`).
+    pub fn to_number(&self) -> f64 {
+        match self {
+            Value::Null => 0.0,
+            Value::Boolean(value) => f64::from(u8::from(*value)),
+            Value::Number(value) => *value,
+            Value::String(value) => parse_number(value.trim()).unwrap_or(f64::NAN),
+            Value::Array(_) | Value::Object(_) => f64::NAN,
+        }
+    }
+
+    /// Looks a property up the way expressions do, ignoring case.
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        match self {
+            Value::Object(entries) => entries
+                .iter()
+                .find(|(key, _)| key.to_lowercase() == name.to_lowercase())
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    /// `value[index]`: an array item by number, or an object property by name.
+    fn lookup(&self, index: &Value) -> Option<&Value> {
+        match self {
+            Value::Array(items) => {
+                let index = index.to_number();
+                if index >= 0.0 && index.fract() == 0.0 {
+                    items.get(index as usize)
+                } else {
+                    None
+                }
+            }
+            Value::Object(_) => self.get(&index.to_string()),
+            _ => None,
+        }
+    }
+
+    fn equals(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Null, Value::Null) => true,
+            (Value::Boolean(a), Value::Boolean(b)) => a == b,
+            (Value::String(a), Value::String(b)) => a.to_uppercase() == b.to_uppercase(),
+            // an array or object is only equal to itself, which an expression
+            // cannot refer to twice
+            (Value::Array(_) | Value::Object(_), _) | (_, Value::Array(_) | Value::Object(_)) => {
+                false
+            }
+            (a, b) => a.to_number() == b.to_number(),
+        }
+    }
+
+    fn compare(&self, other: &Value) -> Option<Ordering> {
+        match (self, other) {
+            (Value::String(a), Value::String(b)) => Some(a.to_uppercase().cmp(&b.to_uppercase())),
+            (a, b) => a.to_number().partial_cmp(&b.to_number()),
+        }
+    }
+}
+
+/// Reads number literals and numeric strings: decimals, exponents and `0x` hex.
+fn parse_number(text: &str) -> Option<f64> {
+    if text.is_empty() {
+        return Some(0.0);
+    }
+    let (sign, unsigned) = match text.strip_prefix('-') {
+        Some(unsigned) => (-1.0, unsigned),
+        None => (1.0, text.strip_prefix('+').unwrap_or(text)),
+    };
+    if let Some(hex) = unsigned.strip_prefix("0x") {
+        return i64::from_str_radix(hex, 16)
+            .ok()
+            .map(|value| sign * value as f64);
+    }
+    if unsigned == "Infinity" {
+        return Some(sign * f64::INFINITY);
+    }
+    let numeric = unsigned.starts_with(|c: char| c.is_ascii_digit() || c == '.')
+        && unsigned
+            .chars()
+            .all(|c| c.is_ascii_digit() || matches!(c, '.' | 'e' | 'E' | '+' | '-'));
+    if !numeric {
+        return None;
+    }
+    unsigned.parse::<f64>().ok().map(|value| sign * value)
+}
+
+/// The value as it is written into text, e.g. by `${{ }}` in a `run` script.
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Null => Ok(()),
+            Value::Boolean(value) => write!(f, "{value}"),
+            Value::Number(value) if value.is_nan() => f.write_str("NaN"),
+            Value::Number(value) if value.is_infinite() => f.write_str(if *value > 0.0 {
+                "Infinity"
+            } else {
+                "-Infinity"
+            }),
+            Value::Number(value) => write!(f, "{value}"),
+            Value::String(value) => f.write_str(value),
+            Value::Array(_) => f.write_str("Array"),
+            Value::Object(_) => f.write_str("Object"),
+        }
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Boolean(value)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Value::Number(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::String(value.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::String(value)
+    }
+}
+
+impl From<&String> for Value {
+    fn from(value: &String) -> Self {
+        Value::String(value.clone())
+    }
+}
+
+impl<V: Into<Value>> From<Vec<V>> for Value {
+    fn from(items: Vec<V>) -> Self {
+        Value::Array(items.into_iter().map(Into::into).collect())
+    }
+}
+
+impl From<serde_json::Value> for Value {
+    fn from(value: serde_json::Value) -> Self {
+        match value {
+            serde_json::Value::Null => Value::Null,
+            serde_json::Value::Bool(value) => Value::Boolean(value),
+            serde_json::Value::Number(value) => Value::Number(value.as_f64().unwrap_or(f64::NAN)),
+            serde_json::Value::String(value) => Value::String(value),
+            serde_json::Value::Array(items) => Value::from(items),
+            serde_json::Value::Object(entries) => Value::object(entries),
+        }
+    }
+}
+
+/// Numbers without a fraction are written as integers, and those JSON cannot
+/// hold (`NaN` and infinities) as `null`.
+impl From<&Value> for serde_json::Value {
+    fn from(value: &Value) -> Self {
+        match value {
+            Value::Null => serde_json::Value::Null,
+            Value::Boolean(value) => serde_json::Value::Bool(*value),
+            Value::Number(value) if value.fract() == 0.0 && value.abs() < 9007199254740992.0 => {
+                serde_json::Value::from(*value as i64)
+            }
+            Value::Number(value) => serde_json::Value::from(*value),
+            Value::String(value) => serde_json::Value::String(value.clone()),
+            Value::Array(items) => items.iter().map(serde_json::Value::from).collect(),
+            Value::Object(entries) => entries
+                .iter()
+                .map(|(key, value)| (key.clone(), serde_json::Value::from(value)))
+                .collect(),
+        }
+    }
+}
+
+impl From<&crate::InputValue> for Value {
+    fn from(value: &crate::InputValue) -> Self {
+        match value {
+            crate::InputValue::Boolean(value) => Value::Boolean(*value),
+            crate::InputValue::Number(value) => Value::Number(*value),
+            crate::InputValue::String(value) => Value::from(value),
+        }
+    }
+}
+
+/// The contexts an expression is evaluated against, by name.
+#[derive(Debug, Clone, Default)]
+pub struct Contexts {
+    named: IndexMap<String, Value>,
+}
+
+impl Contexts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, name: impl Into<String>, value: impl Into<Value>) {
+        self.named.insert(name.into().to_lowercase(), value.into());
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.named.get(&name.to_lowercase())
+    }
+}
+
+/// An expression that could not be parsed or evaluated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpressionError {
+    pub message: String,
+    /// 1-based position, in characters, of what the error is about.
+    pub position: usize,
+    pub expression: String,
+}
+
+impl ExpressionError {
+    /// An error about what starts at byte `offset` of `expression`.
+    fn at(message: String, expression: &str, offset: usize) -> Self {
+        ExpressionError {
+            message,
+            position: expression[..offset].chars().count() + 1,
+            expression: expression.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ExpressionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}. Located at position {} within expression: {}",
+            self.message, self.position, self.expression
+        )
+    }
+}
+
+impl std::error::Error for ExpressionError {}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Literal(Value),
+    Identifier,
+    Dot,
+    Star,
+    Comma,
+    OpenParen,
+    CloseParen,
+    OpenBracket,
+    CloseBracket,
+    Not,
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    And,
+    Or,
+}
+
+/// A token and the bytes of the expression it was read from.
+#[derive(Debug, Clone)]
+struct Lexeme {
+    token: Token,
+    start: usize,
+    end: usize,
+}
+
+fn is_identifier_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_'
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-'
+}
+
+fn tokenize(source: &str) -> Result<Vec<Lexeme>, ExpressionError> {
+    let mut lexemes = Vec::new();
+    let mut chars = source.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        if c.is_whitespace() {
+            continue;
+        }
+        let rest = &source[start..];
+        let next = rest[c.len_utf8()..].chars().next();
+        let token = match (c, next) {
+            ('=', Some('=')) => Token::Equal,
+            ('!', Some('=')) => Token::NotEqual,
+            ('<', Some('=')) => Token::LessOrEqual,
+            ('>', Some('=')) => Token::GreaterOrEqual,
+            ('&', Some('&')) => Token::And,
+            ('|', Some('|')) => Token::Or,
+            ('!', _) => Token::Not,
+            ('<', _) => Token::Less,
+            ('>', _) => Token::Greater,
+            ('*', _) => Token::Star,
+            (',', _) => Token::Comma,
+            ('(', _) => Token::OpenParen,
+            (')', _) => Token::CloseParen,
+            ('[', _) => Token::OpenBracket,
+            (']', _) => Token::CloseBracket,
+            ('.', next) if !next.is_some_and(|c| c.is_ascii_digit()) => Token::Dot,
+            ('\'', _) => {
+                // '' is a quote inside a string
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        None => {
+                            return Err(ExpressionError::at(
+                                format!("Unterminated string: {rest}"),
+                                source,
+                                start,
+                            ))
+                        }
+                        Some((_, '\'')) if chars.peek().is_some_and(|(_, c)| *c == '\'') => {
+                            chars.next();
+                            value.push('\'');
+                        }
+                        Some((_, '\'')) => break,
+                        Some((_, c)) => value.push(c),
+                    }
+                }
+                Token::Literal(Value::String(value))
+            }
+            (c, next)
+                if c.is_ascii_digit()
+                    || (matches!(c, '-' | '+' | '.')
+                        && next.is_some_and(|c| c.is_ascii_digit() || c == '.')) =>
+            {
+                let hex = rest.trim_start_matches(['-', '+']).starts_with("0x");
+                let mut previous = c;
+                while let Some(&(_, c)) = chars.peek() {
+                    let signed_exponent =
+                        !hex && matches!(previous, 'e' | 'E') && matches!(c, '+' | '-');
+                    if !(c.is_ascii_alphanumeric() || c == '.' || signed_exponent) {
+                        break;
+                    }
+                    previous = c;
+                    chars.next();
+                }
+                let end = chars.peek().map_or(source.len(), |(end, _)| *end);
+                let text = &source[start..end];
+                let value = parse_number(text).ok_or_else(|| {
+                    ExpressionError::at(format!("Unexpected symbol: '{text}'"), source, start)
+                })?;
+                Token::Literal(Value::Number(value))
+            }
+            (c, _) if is_identifier_start(c) => {
+                while chars.peek().is_some_and(|(_, c)| is_identifier_char(*c)) {
+                    chars.next();
+                }
+                let end = chars.peek().map_or(source.len(), |(end, _)| *end);
+                match &source[start..end] {
+                    "null" => Token::Literal(Value::Null),
+                    "true" => Token::Literal(Value::Boolean(true)),
+                    "false" => Token::Literal(Value::Boolean(false)),
+                    "NaN" => Token::Literal(Value::Number(f64::NAN)),
+                    "Infinity" => Token::Literal(Value::Number(f64::INFINITY)),
+                    _ => Token::Identifier,
+                }
+            }
+            (c, _) => {
+                return Err(ExpressionError::at(
+                    format!("Unexpected symbol: '{c}'"),
+                    source,
+                    start,
+                ))
+            }
+        };
+        if matches!(
+            token,
+            Token::Equal
+                | Token::NotEqual
+                | Token::LessOrEqual
+                | Token::GreaterOrEqual
+                | Token::And
+                | Token::Or
+        ) {
+            chars.next();
+        }
+        let end = chars.peek().map_or(source.len(), |(end, _)| *end);
+        lexemes.push(Lexeme { token, start, end });
+    }
+    Ok(lexemes)
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Literal(Value),
+    Context(String),
+    Property(Box<Expr>, String),
+    Index(Box<Expr>, Box<Expr>),
+    /// `.*` or `[*]`: the items of an array, or the values of an object.
+    Filter(Box<Expr>),
+    Call {
+        function: &'static Function,
+        args: Vec<Expr>,
+        /// Where the call starts, to report the errors of the function.
+        start: usize,
+    },
+    Not(Box<Expr>),
+    Compare(Box<Expr>, Token, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    fn calls_status_function(&self) -> bool {
+        match self {
+            Expr::Literal(_) | Expr::Context(_) => false,
+            Expr::Call { function, args, .. } => {
+                function.is_status() || args.iter().any(Expr::calls_status_function)
+            }
+            Expr::Property(expr, _) | Expr::Filter(expr) | Expr::Not(expr) => {
+                expr.calls_status_function()
+            }
+            Expr::Index(left, right)
+            | Expr::Compare(left, _, right)
+            | Expr::And(left, right)
+            | Expr::Or(left, right) => {
+                left.calls_status_function() || right.calls_status_function()
+            }
+        }
+    }
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    lexemes: Vec<Lexeme>,
+    next: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.lexemes.get(self.next).map(|lexeme| &lexeme.token)
+    }
+
+    fn advance(&mut self) -> Result<Lexeme, ExpressionError> {
+        let lexeme = self.lexemes.get(self.next).cloned().ok_or_else(|| {
+            ExpressionError::at(
+                "Unexpected end of expression".to_string(),
+                self.source,
+                self.source.trim_end().len(),
+            )
+        })?;
+        self.next += 1;
+        Ok(lexeme)
+    }
+
+    fn unexpected(&self, lexeme: &Lexeme) -> ExpressionError {
+        ExpressionError::at(
+            format!(
+                "Unexpected symbol: '{}'",
+                &self.source[lexeme.start..lexeme.end]
+            ),
+            self.source,
+            lexeme.start,
+        )
+    }
+
+    fn expect(&mut self, token: Token) -> Result<Lexeme, ExpressionError> {
+        let lexeme = self.advance()?;
+        if lexeme.token == token {
+            Ok(lexeme)
+        } else {
+            Err(self.unexpected(&lexeme))
+        }
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        let found = self.peek() == Some(token);
+        if found {
+            self.next += 1;
+        }
+        found
+    }
+
+    fn parse(mut self) -> Result<Expr, ExpressionError> {
+        let expr = self.or()?;
+        match self.lexemes.get(self.next) {
+            Some(lexeme) => Err(self.unexpected(lexeme)),
+            None => Ok(expr),
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr, ExpressionError> {
+        let mut expr = self.and()?;
+        while self.eat(&Token::Or) {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, ExpressionError> {
+        let mut expr = self.equality()?;
+        while self.eat(&Token::And) {
+            expr = Expr::And(Box::new(expr), Box::new(self.equality()?));
+        }
+        Ok(expr)
+    }
+
+    fn equality(&mut self) -> Result<Expr, ExpressionError> {
+        let mut expr = self.comparison()?;
+        while let Some(operator @ (Token::Equal | Token::NotEqual)) = self.peek().cloned() {
+            self.next += 1;
+            expr = Expr::Compare(Box::new(expr), operator, Box::new(self.comparison()?));
+        }
+        Ok(expr)
+    }
+
+    fn comparison(&mut self) -> Result<Expr, ExpressionError> {
+        let mut expr = self.unary()?;
+        while let Some(
+            operator @ (Token::Less | Token::LessOrEqual | Token::Greater | Token::GreaterOrEqual),
+        ) = self.peek().cloned()
+        {
+            self.next += 1;
+            expr = Expr::Compare(Box::new(expr), operator, Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr, ExpressionError> {
+        if self.eat(&Token::Not) {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        let mut expr = self.primary()?;
+        loop {
+            if self.eat(&Token::Dot) {
+                let lexeme = self.advance()?;
+                let name = &self.source[lexeme.start..lexeme.end];
+                expr = if lexeme.token == Token::Star {
+                    Expr::Filter(Box::new(expr))
+                } else if name.starts_with(is_identifier_start)
+                    && name.chars().all(is_identifier_char)
+                {
+                    Expr::Property(Box::new(expr), name.to_string())
+                } else {
+                    return Err(self.unexpected(&lexeme));
+                };
+            } else if self.eat(&Token::OpenBracket) {
+                expr = if self.eat(&Token::Star) {
+                    Expr::Filter(Box::new(expr))
+                } else {
+                    Expr::Index(Box::new(expr), Box::new(self.or()?))
+                };
+                self.expect(Token::CloseBracket)?;
+            } else {
+                return Ok(expr);
+            }
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr, ExpressionError> {
+        let lexeme = self.advance()?;
+        match lexeme.token {
+            Token::Literal(value) => Ok(Expr::Literal(value)),
+            Token::OpenParen => {
+                let expr = self.or()?;
+                self.expect(Token::CloseParen)?;
+                Ok(expr)
+            }
+            Token::Identifier => {
+                let name = &self.source[lexeme.start..lexeme.end];
+                if self.eat(&Token::OpenParen) {
+                    self.call(name, lexeme.start)
+                } else if CONTEXT_NAMES
+                    .iter()
+                    .any(|context| context.eq_ignore_ascii_case(name))
+                {
+                    Ok(Expr::Context(name.to_string()))
+                } else {
+                    Err(ExpressionError::at(
+                        format!("Unrecognized named-value: '{name}'"),
+                        self.source,
+                        lexeme.start,
+                    ))
+                }
+            }
+            _ => Err(self.unexpected(&lexeme)),
+        }
+    }
+
+    /// The arguments of a call, after its `(`.
+    fn call(&mut self, name: &str, start: usize) -> Result<Expr, ExpressionError> {
+        let error = |message: String| ExpressionError::at(message, self.source, start);
+        let function = functions::find(name)
+            .ok_or_else(|| error(format!("Unrecognized function: '{name}'")))?;
+        let mut args = Vec::new();
+        if !self.eat(&Token::CloseParen) {
+            loop {
+                args.push(self.or()?);
+                if self.eat(&Token::CloseParen) {
+                    break;
+                }
+                self.expect(Token::Comma)?;
+            }
+        }
+        if args.len() < function.min_args {
+            return Err(error(format!("Too few parameters supplied: '{name}'")));
+        }
+        if args.len() > function.max_args {
+            return Err(error(format!("Too many parameters supplied: '{name}'")));
+        }
+        Ok(Expr::Call {
+            function,
+            args,
+            start,
+        })
+    }
+}
+
+/// An intermediate result: `.*` gives a filtered array, on which property
+/// access and indexing apply to each item.
+enum Operand {
+    Value(Value),
+    Filtered(Vec<Value>),
+}
+
+impl Operand {
+    fn into_value(self) -> Value {
+        match self {
+            Operand::Value(value) => value,
+            Operand::Filtered(items) => Value::Array(items),
+        }
+    }
+}
+
+/// A parsed expression, without its `${{ }}`.
+#[derive(Debug, Clone)]
+pub struct Expression {
+    source: String,
+    root: Expr,
+}
+
+impl Expression {
+    pub fn parse(source: &str) -> Result<Self, ExpressionError> {
+        let root = Parser {
+            source,
+            lexemes: tokenize(source)?,
+            next: 0,
+        }
+        .parse()?;
+        Ok(Expression {
+            source: source.to_string(),
+            root,
+        })
+    }
+
+    /// Parses an `if:` condition, written with or without `${{ }}`. Unless it
+    /// calls a status function such as `always()`, it only holds while the job
+    /// succeeds, as if it were `success() && (condition)`.
+    pub fn parse_condition(condition: &str) -> Result<Self, ExpressionError> {
+        let condition = condition.trim();
+        let source = condition
+            .strip_prefix("${{")
+            .and_then(|inner| {
+                let body = inner.strip_suffix("}}")?;
+                (expression_end(inner) == Some(body.len())).then_some(body.trim())
+            })
+            .unwrap_or(condition);
+        let mut expression = Self::parse(source)?;
+        if !expression.root.calls_status_function() {
+            let success = Expr::Call {
+                function: functions::find("success").expect("success() is a function"),
+                args: Vec::new(),
+                start: 0,
+            };
+            expression.root = Expr::And(Box::new(success), Box::new(expression.root));
+        }
+        Ok(expression)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    pub fn evaluate(&self, contexts: &Contexts) -> Result<Value, ExpressionError> {
+        self.value(&self.root, contexts)
+    }
+
+    fn value(&self, expr: &Expr, contexts: &Contexts) -> Result<Value, ExpressionError> {
+        Ok(self.eval(expr, contexts)?.into_value())
+    }
+
+    fn eval(&self, expr: &Expr, contexts: &Contexts) -> Result<Operand, ExpressionError> {
+        let value = match expr {
+            Expr::Literal(value) => value.clone(),
+            Expr::Context(name) => contexts.get(name).cloned().unwrap_or_else(|| {
+                warn!(
+                    "The `{name}` context is not available locally, `{}` reads it as empty",
+                    self.source
+                );
+                Value::Null
+            }),
+            Expr::Property(base, name) => match self.eval(base, contexts)? {
+                Operand::Value(value) => value.get(name).cloned().unwrap_or_else(|| {
+                    // only part of `github` is known outside of a runner
+                    if matches!(&**base, Expr::Context(context) if context == "github") {
+                        warn!(
+                            "`github.{name}` is not known locally, `{}` reads it as empty",
+                            self.source
+                        );
+                    }
+                    Value::Null
+                }),
+                Operand::Filtered(items) => {
+                    return Ok(Operand::Filtered(
+                        items
+                            .iter()
+                            .filter_map(|item| item.get(name))
+                            .cloned()
+                            .collect(),
+                    ))
+                }
+            },
+            Expr::Index(base, index) => {
+                let base = self.eval(base, contexts)?;
+                let index = self.value(index, contexts)?;
+                match base {
+                    Operand::Value(value) => value.lookup(&index).cloned().unwrap_or_default(),
+                    Operand::Filtered(items) => {
+                        return Ok(Operand::Filtered(
+                            items
+                                .iter()
+                                .filter_map(|item| item.lookup(&index))
+                                .cloned()
+                                .collect(),
+                        ))
+                    }
+                }
+            }
+            Expr::Filter(base) => {
+                let children = |value: Value| match value {
+                    Value::Array(items) => items,
+                    Value::Object(entries) => entries.into_values().collect(),
+                    _ => Vec::new(),
+                };
+                return Ok(Operand::Filtered(match self.eval(base, contexts)? {
+                    Operand::Value(value) => children(value),
+                    Operand::Filtered(items) => items.into_iter().flat_map(children).collect(),
+                }));
+            }
+            Expr::Call {
+                function,
+                args,
+                start,
+            } => {
+                let args = args
+                    .iter()
+                    .map(|arg| self.value(arg, contexts))
+                    .collect::<Result<Vec<_>, _>>()?;
+                (function.call)(&args, contexts)
+                    .map_err(|message| ExpressionError::at(message, &self.source, *start))?
+            }
+            Expr::Not(operand) => Value::Boolean(!self.value(operand, contexts)?.is_truthy()),
+            Expr::Compare(left, operator, right) => {
+                let left = self.value(left, contexts)?;
+                let right = self.value(right, contexts)?;
+                let ordering = left.compare(&right);
+                Value::Boolean(match operator {
+                    Token::Equal => left.equals(&right),
+                    Token::NotEqual => !left.equals(&right),
+                    Token::Less => ordering == Some(Ordering::Less),
+                    Token::LessOrEqual => {
+                        matches!(ordering, Some(Ordering::Less | Ordering::Equal))
+                    }
+                    Token::Greater => ordering == Some(Ordering::Greater),
+                    Token::GreaterOrEqual => {
+                        matches!(ordering, Some(Ordering::Greater | Ordering::Equal))
+                    }
+                    _ => unreachable!("not a comparison operator"),
+                })
+            }
+            Expr::And(left, right) => {
+                let left = self.value(left, contexts)?;
+                if left.is_truthy() {
+                    self.value(right, contexts)?
+                } else {
+                    left
+                }
+            }
+            Expr::Or(left, right) => {
+                let left = self.value(left, contexts)?;
+                if left.is_truthy() {
+                    left
+                } else {
+                    self.value(right, contexts)?
+                }
+            }
+        };
+        Ok(Operand::Value(value))
+    }
+}
+
+/// Parses and evaluates an expression written without its `${{ }}`.
+pub fn evaluate(source: &str, contexts: &Contexts) -> Result<Value, ExpressionError> {
+    Expression::parse(source)?.evaluate(contexts)
+}
+
+/// Replaces each `${{ }}` in `text` with the value of its expression.
+pub fn interpolate(text: &str, contexts: &Contexts) -> Result<String, ExpressionError> {
+    let mut result = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("${{") {
+        result.push_str(&rest[..start]);
+        let inner = &rest[start + 3..];
+        let end = expression_end(inner).ok_or_else(|| ExpressionError {
+            message: "Unterminated expression, `}}` is missing".to_string(),
+            position: 1,
+            expression: rest[start..].to_string(),
+        })?;
+        result.push_str(&evaluate(inner[..end].trim(), contexts)?.to_string());
+        rest = &inner[end + 2..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+/// Where the `}}` closing an expression is, skipping those in string literals.
+fn expression_end(text: &str) -> Option<usize> {
+    let mut quoted = false;
+    for (index, c) in text.char_indices() {
+        if c == '\'' {
+            quoted = !quoted;
+        } else if !quoted && text[index..].starts_with("}}") {
+            return Some(index);
+        }
+    }
+    None
+}
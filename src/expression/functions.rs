@@ -0,0 +1,248 @@
+//! The functions expressions can call, such as `contains` or `format`.
+
+use super::{hash_files, Contexts, Value};
+use std::path::PathBuf;
+
+/// A function, with how many arguments it takes.
+#[derive(Debug)]
+pub(super) struct Function {
+    pub name: &'static str,
+    pub min_args: usize,
+    pub max_args: usize,
+    pub call: fn(&[Value], &Contexts) -> Result<Value, String>,
+}
+
+const FUNCTIONS: [Function; 12] = [
+    Function {
+        name: "contains",
+        min_args: 2,
+        max_args: 2,
+        call: contains,
+    },
+    Function {
+        name: "startsWith",
+        min_args: 2,
+        max_args: 2,
+        call: starts_with,
+    },
+    Function {
+        name: "endsWith",
+        min_args: 2,
+        max_args: 2,
+        call: ends_with,
+    },
+    Function {
+        name: "format",
+        min_args: 1,
+        max_args: usize::MAX,
+        call: format,
+    },
+    Function {
+        name: "join",
+        min_args: 1,
+        max_args: 2,
+        call: join,
+    },
+    Function {
+        name: "toJSON",
+        min_args: 1,
+        max_args: 1,
+        call: to_json,
+    },
+    Function {
+        name: "fromJSON",
+        min_args: 1,
+        max_args: 1,
+        call: from_json,
+    },
+    Function {
+        name: "hashFiles",
+        min_args: 1,
+        max_args: usize::MAX,
+        call: hash_files_function,
+    },
+    Function {
+        name: "success",
+        min_args: 0,
+        max_args: 0,
+        call: success,
+    },
+    Function {
+        name: "failure",
+        min_args: 0,
+        max_args: 0,
+        call: failure,
+    },
+    Function {
+        name: "always",
+        min_args: 0,
+        max_args: 0,
+        call: always,
+    },
+    Function {
+        name: "cancelled",
+        min_args: 0,
+        max_args: 0,
+        call: cancelled,
+    },
+];
+
+/// The function called `name`, which is case-insensitive.
+pub(super) fn find(name: &str) -> Option<&'static Function> {
+    FUNCTIONS
+        .iter()
+        .find(|function| function.name.eq_ignore_ascii_case(name))
+}
+
+impl Function {
+    /// Whether the function tells how the job is doing, which lets a condition
+    /// hold after a failure.
+    pub fn is_status(&self) -> bool {
+        matches!(self.name, "success" | "failure" | "always" | "cancelled")
+    }
+}
+
+/// `contains(search, item)`: whether an array has an item equal to `item`,
+/// or a string contains `item`, ignoring case.
+fn contains(args: &[Value], _: &Contexts) -> Result<Value, String> {
+    let found = match &args[0] {
+        Value::Array(items) => items.iter().any(|value| value.equals(&args[1])),
+        search => upper(search).contains(&upper(&args[1])),
+    };
+    Ok(Value::Boolean(found))
+}
+
+fn starts_with(args: &[Value], _: &Contexts) -> Result<Value, String> {
+    Ok(Value::Boolean(
+        upper(&args[0]).starts_with(&upper(&args[1])),
+    ))
+}
+
+fn ends_with(args: &[Value], _: &Contexts) -> Result<Value, String> {
+    Ok(Value::Boolean(upper(&args[0]).ends_with(&upper(&args[1]))))
+}
+
+fn upper(value: &Value) -> String {
+    value.to_string().to_uppercase()
+}
+
+/// `format('{0} {1}', a, b)`: replaces `{N}` with the N-th argument after the
+/// format; `{{` and `}}` stand for braces.
+fn format(args: &[Value], _: &Contexts) -> Result<Value, String> {
+    let format = args[0].to_string();
+    let invalid = || format!("The following format string is invalid: {format}");
+    let mut result = String::new();
+    let mut chars = format.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                result.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                result.push('}');
+            }
+            '{' => {
+                let mut index = String::new();
+                while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+                    index.push(digit);
+                }
+                if index.is_empty() || chars.next() != Some('}') {
+                    return Err(invalid());
+                }
+                let value = index
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|index| args.get(index + 1))
+                    .ok_or_else(|| {
+                        format!(
+                            "The following format string references more arguments than were supplied: {format}"
+                        )
+                    })?;
+                result.push_str(&value.to_string());
+            }
+            '}' => return Err(invalid()),
+            c => result.push(c),
+        }
+    }
+    Ok(Value::String(result))
+}
+
+/// `join(array, separator)`: the items of an array as strings, separated by
+/// `separator`, a comma by default.
+fn join(args: &[Value], _: &Contexts) -> Result<Value, String> {
+    let separator = args.get(1).map_or(",".to_string(), Value::to_string);
+    Ok(Value::String(match &args[0] {
+        Value::Array(items) => items
+            .iter()
+            .map(Value::to_string)
+            .collect::<Vec<_>>()
+            .join(&separator),
+        value => value.to_string(),
+    }))
+}
+
+/// `toJSON(value)`: the value as pretty-printed JSON.
+fn to_json(args: &[Value], _: &Contexts) -> Result<Value, String> {
+    serde_json::to_string_pretty(&serde_json::Value::from(&args[0]))
+        .map(Value::String)
+        .map_err(|err| err.to_string())
+}
+
+/// `fromJSON(text)`: the value a JSON document holds, e.g. to turn `'true'`
+/// into a boolean or build a matrix from a string.
+fn from_json(args: &[Value], _: &Contexts) -> Result<Value, String> {
+    let text = args[0].to_string();
+    serde_json::from_str::<serde_json::Value>(text.trim())
+        .map(Value::from)
+        .map_err(|err| format!("Invalid JSON given to fromJSON: {err}"))
+}
+
+/// `hashFiles(pattern, ...)`: see [`hash_files`], with the files looked up in
+/// `github.workspace`, or the current directory if it is not set.
+fn hash_files_function(args: &[Value], contexts: &Contexts) -> Result<Value, String> {
+    let workspace = match contexts
+        .get("github")
+        .and_then(|github| github.get("workspace"))
+    {
+        Some(workspace) => PathBuf::from(workspace.to_string()),
+        None => std::env::current_dir().map_err(|err| err.to_string())?,
+    };
+    let patterns: Vec<String> = args.iter().map(Value::to_string).collect();
+    let patterns: Vec<&str> = patterns.iter().map(String::as_str).collect();
+    hash_files(&workspace, &patterns)
+        .map(Value::String)
+        .map_err(|err| {
+            format!(
+                "hashFiles failed to hash the files of {}: {err}",
+                workspace.display()
+            )
+        })
+}
+
+/// `job.status`: `success` unless a step failed (`failure`) or the run was
+/// cancelled (`cancelled`). For the `if:` of a job it is the worst result of
+/// the jobs it needs, where `skipped` leaves only `always()` true.
+fn job_status(contexts: &Contexts) -> String {
+    contexts
+        .get("job")
+        .and_then(|job| job.get("status"))
+        .map_or("success".to_string(), Value::to_string)
+}
+
+fn success(_: &[Value], contexts: &Contexts) -> Result<Value, String> {
+    Ok(Value::Boolean(job_status(contexts) == "success"))
+}
+
+fn failure(_: &[Value], contexts: &Contexts) -> Result<Value, String> {
+    Ok(Value::Boolean(job_status(contexts) == "failure"))
+}
+
+fn always(_: &[Value], _: &Contexts) -> Result<Value, String> {
+    Ok(Value::Boolean(true))
+}
+
+fn cancelled(_: &[Value], contexts: &Contexts) -> Result<Value, String> {
+    Ok(Value::Boolean(job_status(contexts) == "cancelled"))
+}
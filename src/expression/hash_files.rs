@@ -0,0 +1,166 @@
+//! `hashFiles()`, computed the way the runner does so that cache keys built
+//! locally match those built in CI for the same files.
+
+use globset::{GlobBuilder, GlobMatcher};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+/// A line of the patterns given to `hashFiles`, relative to the workspace.
+struct Pattern {
+    negate: bool,
+    /// `None` when the pattern ends with `/`, which only matches directories.
+    glob: Option<GlobMatcher>,
+    /// Files under a matching directory match too.
+    descendants: GlobMatcher,
+    /// Directory the matches are looked for in.
+    search_path: PathBuf,
+}
+
+impl Pattern {
+    fn parse(line: &str, workspace: &Path) -> Option<Self> {
+        let mut pattern = line.trim();
+        if pattern.is_empty() || pattern.starts_with('#') {
+            return None;
+        }
+        let mut negate = false;
+        while let Some(rest) = pattern.strip_prefix('!') {
+            negate = !negate;
+            pattern = rest;
+        }
+        let absolute = Path::new(pattern);
+        if absolute.is_absolute() {
+            pattern = absolute.strip_prefix(workspace).ok()?.to_str()?;
+        }
+        let pattern = pattern.trim_start_matches("./");
+        let (pattern, directory_only) = match pattern.strip_suffix('/') {
+            Some(directory) => (directory, true),
+            None => (pattern, false),
+        };
+        // braces are literal in the runner's patterns
+        let pattern = pattern.replace('{', "[{]").replace('}', "[}]");
+        let matcher = |pattern: &str| {
+            GlobBuilder::new(pattern)
+                .literal_separator(true)
+                .build()
+                .ok()
+                .map(|glob| glob.compile_matcher())
+        };
+        let literal: Vec<&str> = pattern
+            .split('/')
+            .take_while(|segment| !segment.contains(['*', '?', '[']))
+            .collect();
+        Some(Pattern {
+            negate,
+            glob: if directory_only {
+                None
+            } else {
+                Some(matcher(&pattern)?)
+            },
+            descendants: matcher(&format!("{pattern}/**"))?,
+            search_path: workspace.join(literal.join("/")),
+        })
+    }
+
+    fn is_match(&self, relative: &str) -> bool {
+        self.glob
+            .as_ref()
+            .is_some_and(|glob| glob.is_match(relative))
+            || self.descendants.is_match(relative)
+    }
+}
+
+/// The `hashFiles` hash of the files under `workspace` matching `patterns`:
+/// each line is a glob, `!` excludes what earlier lines matched, and the
+/// result is the SHA-256 of the SHA-256 of each file, in the order they are
+/// found. It is empty when no file matches.
+pub fn hash_files(workspace: &Path, patterns: &[&str]) -> io::Result<String> {
+    let patterns: Vec<Pattern> = patterns
+        .iter()
+        .flat_map(|patterns| patterns.lines())
+        .filter_map(|line| Pattern::parse(line, workspace))
+        .collect();
+    let mut hasher = Sha256::new();
+    let mut count = 0;
+    for file in files(&patterns)? {
+        // the last pattern matching a file decides whether it is hashed
+        let included = relative_path(workspace, &file).is_some_and(|relative| {
+            patterns
+                .iter()
+                .rev()
+                .find(|pattern| pattern.is_match(&relative))
+                .is_some_and(|pattern| !pattern.negate)
+        });
+        if !included {
+            continue;
+        }
+        let contents = fs::read(&file)?;
+        hasher.update(Sha256::digest(contents));
+        count += 1;
+    }
+    Ok(if count == 0 {
+        String::new()
+    } else {
+        format!("{:x}", hasher.finalize())
+    })
+}
+
+/// `path` relative to `workspace` with `/` separators, if it is inside it.
+fn relative_path(workspace: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(workspace).ok()?;
+    let mut segments = Vec::new();
+    for component in relative.components() {
+        match component {
+            Component::Normal(segment) => segments.push(segment.to_str()?),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    Some(segments.join("/"))
+}
+
+/// The files in the search paths of `patterns`, depth first with the entries
+/// of each directory sorted by name, as the runner walks them. Search paths
+/// inside another one are only walked once, as part of it.
+fn files(patterns: &[Pattern]) -> io::Result<Vec<PathBuf>> {
+    let candidates: HashSet<&Path> = patterns
+        .iter()
+        .filter(|pattern| !pattern.negate)
+        .map(|pattern| pattern.search_path.as_path())
+        .collect();
+    let mut search_paths: Vec<&Path> = Vec::new();
+    for pattern in patterns.iter().filter(|pattern| !pattern.negate) {
+        let path = pattern.search_path.as_path();
+        let nested = path
+            .ancestors()
+            .skip(1)
+            .any(|ancestor| candidates.contains(ancestor));
+        if !nested && !search_paths.contains(&path) {
+            search_paths.push(path);
+        }
+    }
+
+    let mut files = Vec::new();
+    let mut visited = HashSet::new();
+    for search_path in search_paths {
+        let mut stack = vec![search_path.to_path_buf()];
+        while let Some(path) = stack.pop() {
+            // symbolic links are followed
+            let Ok(metadata) = fs::metadata(&path) else {
+                continue;
+            };
+            if metadata.is_file() {
+                files.push(path);
+            } else if metadata.is_dir() && visited.insert(fs::canonicalize(&path)?) {
+                let mut children = fs::read_dir(&path)?
+                    .map(|entry| entry.map(|entry| entry.path()))
+                    .collect::<io::Result<Vec<_>>>()?;
+                children.sort();
+                stack.extend(children.into_iter().rev());
+            }
+        }
+    }
+    Ok(files)
+}
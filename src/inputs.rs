@@ -0,0 +1,108 @@
+//! Inputs a workflow declares under `workflow_dispatch` and `workflow_call`, and
+//! the values a run receives for them.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A declared input, e.g. `on.workflow_dispatch.inputs.environment`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Input {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub required: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<InputValue>,
+    #[serde(rename = "type", default)]
+    pub input_type: InputType,
+    /// The values a `choice` input accepts.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub options: Vec<String>,
+    #[serde(rename = "deprecationMessage", skip_serializing_if = "Option::is_none")]
+    pub deprecation_message: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum InputType {
+    #[default]
+    String,
+    Boolean,
+    Number,
+    /// One of the input's `options`.
+    Choice,
+    /// The name of one of the repository's environments.
+    Environment,
+}
+
+impl fmt::Display for InputType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            InputType::String => "string",
+            InputType::Boolean => "boolean",
+            InputType::Number => "number",
+            InputType::Choice => "choice",
+            InputType::Environment => "environment",
+        };
+        f.write_str(name)
+    }
+}
+
+/// The value of an input, typed as declared.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum InputValue {
+    Boolean(bool),
+    Number(#[serde(serialize_with = "crate::de::serialize_number")] f64),
+    String(String),
+}
+
+impl fmt::Display for InputValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InputValue::Boolean(value) => write!(f, "{value}"),
+            InputValue::Number(value) => write!(f, "{value}"),
+            InputValue::String(value) => f.write_str(value),
+        }
+    }
+}
+
+impl Input {
+    /// Reads a value given on the command line or typed at a prompt, checking it
+    /// against the declared type and options.
+    pub fn parse(&self, value: &str) -> Result<InputValue, String> {
+        match self.input_type {
+            InputType::Boolean => match value {
+                "true" => Ok(InputValue::Boolean(true)),
+                "false" => Ok(InputValue::Boolean(false)),
+                _ => Err(format!("expected `true` or `false`, got `{value}`")),
+            },
+            InputType::Number => value
+                .trim()
+                .parse()
+                .map(InputValue::Number)
+                .map_err(|_| format!("expected a number, got `{value}`")),
+            InputType::Choice if !self.options.iter().any(|option| option == value) => Err(
+                format!("expected one of {}, got `{value}`", self.options.join(", ")),
+            ),
+            InputType::String | InputType::Choice | InputType::Environment => {
+                Ok(InputValue::String(value.to_string()))
+            }
+        }
+    }
+
+    /// The value used when none is given: the declared default, or what GitHub
+    /// passes for an omitted optional input. The default is checked like a
+    /// given value, so `default: 'false'` on a boolean input is `false`.
+    pub fn default_value(&self) -> Result<Option<InputValue>, String> {
+        match (&self.default, self.input_type) {
+            (Some(default), _) => self
+                .parse(&default.to_string())
+                .map(Some)
+                .map_err(|reason| format!("its default is invalid: {reason}")),
+            (None, InputType::Boolean) => Ok(Some(InputValue::Boolean(false))),
+            (None, _) => Ok(None),
+        }
+    }
+}
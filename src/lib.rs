@@ -1,15 +1,80 @@
-use log::{error, info, warn};
+use indexmap::IndexMap;
+use log::{debug, info, warn};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::fmt;
 use std::io::{BufRead, BufReader};
-use std::process::Command;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::thread;
+use std::time::{Duration, Instant};
 
-#[derive(Debug, Serialize, Deserialize)]
+pub mod action;
+pub mod builder;
+pub mod container;
+mod de;
+pub mod document;
+pub mod error;
+pub mod events;
+pub mod expression;
+pub mod inputs;
+pub mod permissions;
+pub mod repository;
+pub mod runs_on;
+pub mod schema;
+pub mod source;
+mod strict;
+pub mod uses;
+
+pub use action::{Action, ActionInput, ActionOutput, Runs};
+pub use builder::{JobBuilder, StepBuilder, WorkflowBuilder};
+pub use container::{Container, Credentials, LocalLimitation};
+pub use document::{Document, EnvTarget};
+pub use error::{Error, Result};
+pub use events::Trigger;
+pub use expression::{Contexts, Expression, ExpressionError, Value};
+pub use inputs::{Input, InputType, InputValue};
+pub use permissions::{Access, EffectivePermissions, Permissions, PermissionsSource, Scope};
+pub use runs_on::RunsOn;
+pub use source::{Location, ParseError, SourceMap, Span};
+pub use strict::UnknownKey;
+pub use uses::Uses;
+
+/// How strictly workflow files are read.
+#[derive(Debug, Clone, Default)]
+pub struct ParseOptions {
+    /// Reject keys GitHub does not accept instead of silently ignoring them.
+    pub strict: bool,
+}
+
+/// A workflow file. Fields are declared, and serialized, in the order they are
+/// usually written in.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Workflow {
-    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[schemars(schema_with = "events::trigger_schema")]
     pub on: Trigger,
-    pub jobs: std::collections::HashMap<String, Job>,
-    pub env: Option<std::collections::HashMap<String, String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub permissions: Option<Permissions>,
+    #[serde(
+        default,
+        deserialize_with = "de::scalar_map",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(with = "Option<de::ScalarMap>")]
+    pub env: Option<IndexMap<String, String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub defaults: Option<Defaults>,
+    pub jobs: IndexMap<String, Job>,
+    /// File the workflow was loaded from, if any.
+    #[serde(skip)]
+    pub path: Option<PathBuf>,
+    /// Where every key and value of the workflow was read from.
+    #[serde(skip)]
+    pub source_map: SourceMap,
 }
 
 impl Workflow {
@@ -17,32 +82,435 @@ impl Workflow {
         self.jobs.get(job_name)
     }
 
-    pub fn from_yaml(yaml_data: &str) -> Result<Self, serde_yaml::Error> {
-        serde_yaml::from_str(yaml_data)
+    pub fn from_yaml(yaml_data: &str) -> Result<Self, ParseError> {
+        Self::from_yaml_with_options(yaml_data, &ParseOptions::default())
     }
-}
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct Trigger {
-    pub push: Option<Push>,
-    pub pull_request: Option<serde_yaml::Value>, // Using Value for unstructured data
-}
+    pub fn from_yaml_with_options(
+        yaml_data: &str,
+        options: &ParseOptions,
+    ) -> Result<Self, ParseError> {
+        Self::parse(yaml_data, None, options)
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_file_with_options(path, &ParseOptions::default())
+    }
+
+    pub fn from_file_with_options(path: impl AsRef<Path>, options: &ParseOptions) -> Result<Self> {
+        let path = path.as_ref();
+        let yaml_data = std::fs::read_to_string(path).map_err(|source| Error::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Ok(Self::parse(&yaml_data, Some(path.to_path_buf()), options)?)
+    }
+
+    fn parse(
+        yaml_data: &str,
+        path: Option<PathBuf>,
+        options: &ParseOptions,
+    ) -> Result<Self, ParseError> {
+        let root = source::Node::parse(yaml_data, path.clone())?;
+        let source_map = SourceMap::from_node(root.as_ref(), path.clone());
+        // unknown keys are reported first: a misspelled required key would
+        // otherwise only surface as a less helpful "missing field" error
+        if let (true, Some(root)) = (options.strict, &root) {
+            let unknown_keys = strict::unknown_keys(root, &source_map);
+            if !unknown_keys.is_empty() {
+                return Err(ParseError::from_unknown_keys(unknown_keys));
+            }
+        }
+
+        let mut workflow: Workflow = de::from_yaml(yaml_data, &source_map)?;
+        workflow.source_map = source_map;
+        workflow.path = path;
+        for (job_name, job) in workflow.jobs.iter_mut() {
+            job.span = workflow
+                .source_map
+                .key(&format!("jobs.{job_name}"))
+                .cloned();
+            for (index, step) in job.steps.iter_mut().enumerate() {
+                step.span = workflow
+                    .source_map
+                    .value(&format!("jobs.{job_name}.steps[{index}]"))
+                    .cloned();
+            }
+        }
+        Ok(workflow)
+    }
+
+    /// Everything the steps of `job` inherit from the job and from this workflow.
+    /// The job's `timeout-minutes` start counting when this is called.
+    pub fn step_context(
+        &self,
+        job: &Job,
+        secret_vars: Option<std::collections::HashMap<String, String>>,
+    ) -> StepContext {
+        self.step_context_with_inputs(job, secret_vars, IndexMap::new())
+    }
+
+    /// Like [`Workflow::step_context`], for a run given `inputs`, as returned by
+    /// [`Workflow::resolve_inputs`].
+    pub fn step_context_with_inputs(
+        &self,
+        job: &Job,
+        secret_vars: Option<std::collections::HashMap<String, String>>,
+        inputs: IndexMap<String, InputValue>,
+    ) -> StepContext {
+        let mut context = StepContext {
+            defaults: job.run_defaults().or(self.run_defaults()),
+            secret_vars,
+            inputs,
+            ..Default::default()
+        };
+        let env_vars = merge_env(None, self.env.as_ref(), &context);
+        context.env_vars = merge_env(env_vars, job.env.as_ref(), &context);
+        context.deadline = job
+            .timeout_minutes
+            .as_ref()
+            .and_then(|minutes| minutes.deadline(&context.contexts(context.env_vars.as_ref())));
+        context
+    }
+
+    /// Checks the `provided` input values against the inputs the workflow
+    /// declares, and completes them with the declared defaults.
+    pub fn resolve_inputs(
+        &self,
+        provided: &std::collections::HashMap<String, String>,
+    ) -> Result<IndexMap<String, InputValue>> {
+        let declared = self.on.inputs();
+        let invalid = |name: &str, reason: String| Error::InvalidInput {
+            name: name.to_string(),
+            reason,
+        };
+        let mut undeclared: Vec<&String> = provided
+            .keys()
+            .filter(|name| !declared.contains_key(name.as_str()))
+            .collect();
+        undeclared.sort();
+        if let Some(name) = undeclared.first() {
+            return Err(invalid(
+                name,
+                "the workflow does not declare it".to_string(),
+            ));
+        }
+
+        let mut inputs = IndexMap::new();
+        for (name, input) in declared {
+            let value = match provided.get(name) {
+                Some(value) => input.parse(value).map_err(|reason| invalid(name, reason))?,
+                None => match input
+                    .default_value()
+                    .map_err(|reason| invalid(name, reason))?
+                {
+                    Some(value) => value,
+                    None if input.required => {
+                        return Err(invalid(name, "it is required".to_string()))
+                    }
+                    None => continue,
+                },
+            };
+            inputs.insert(name.to_string(), value);
+        }
+        Ok(inputs)
+    }
+
+    fn run_defaults(&self) -> RunDefaults {
+        self.defaults
+            .as_ref()
+            .and_then(|defaults| defaults.run.clone())
+            .unwrap_or_default()
+    }
+
+    /// Jobs in the order they would be scheduled: every job comes after the jobs it
+    /// `needs`, and otherwise in declaration order.
+    pub fn jobs_in_order(&self) -> Result<Vec<(&String, &Job)>> {
+        for (name, job) in &self.jobs {
+            if let Some(missing) = job
+                .needs()
+                .iter()
+                .find(|need| !self.jobs.contains_key(*need))
+            {
+                return Err(Error::MissingNeed {
+                    job: name.clone(),
+                    need: missing.clone(),
+                });
+            }
+        }
+
+        let mut ordered: Vec<(&String, &Job)> = Vec::with_capacity(self.jobs.len());
+        let mut remaining: Vec<(&String, &Job)> = self.jobs.iter().collect();
+        while !remaining.is_empty() {
+            let ready = remaining.iter().position(|(_, job)| {
+                job.needs()
+                    .iter()
+                    .all(|need| ordered.iter().any(|(name, _)| *name == need))
+            });
+            match ready {
+                Some(index) => ordered.push(remaining.remove(index)),
+                None => {
+                    return Err(Error::NeedsCycle {
+                        cycle: self
+                            .find_needs_cycle(&remaining)
+                            .into_iter()
+                            .map(String::from)
+                            .collect(),
+                    });
+                }
+            }
+        }
+        Ok(ordered)
+    }
+
+    /// The jobs `name` needs, directly first and then through them, each once.
+    pub fn job_ancestors(&self, name: &str) -> Vec<&String> {
+        let mut ancestors: Vec<&String> = Vec::new();
+        let mut queue: std::collections::VecDeque<&String> = self
+            .jobs
+            .get(name)
+            .into_iter()
+            .flat_map(|job| job.needs())
+            .collect();
+        while let Some(need) = queue.pop_front() {
+            if ancestors.contains(&need) {
+                continue;
+            }
+            ancestors.push(need);
+            if let Some(job) = self.jobs.get(need) {
+                queue.extend(job.needs());
+            }
+        }
+        ancestors
+    }
+
+    /// The results of the jobs `name` needs directly, for its `needs` context;
+    /// jobs missing from `results` count as skipped.
+    pub fn needs_results(
+        &self,
+        name: &str,
+        results: &std::collections::HashMap<String, JobResult>,
+    ) -> IndexMap<String, JobResult> {
+        self.get_job(name)
+            .into_iter()
+            .flat_map(Job::needs)
+            .map(|need| {
+                let result = results.get(need).copied().unwrap_or(JobResult::Skipped);
+                (need.clone(), result)
+            })
+            .collect()
+    }
+
+    /// Whether the job `name` runs, given the `results` of the jobs before it:
+    /// its `if:` holds, or, without one, every job it needs, directly or not,
+    /// succeeded. The jobs it needs directly are in `needs`, and jobs missing
+    /// from `results` count as skipped.
+    pub fn job_condition_met(
+        &self,
+        name: &str,
+        results: &std::collections::HashMap<String, JobResult>,
+        context: &StepContext,
+    ) -> Result<bool> {
+        let job = self.get_job(name).ok_or_else(|| Error::JobNotFound {
+            job: name.to_string(),
+        })?;
+        let result = |job: &String| results.get(job).copied().unwrap_or(JobResult::Skipped);
+        // the status functions look at every job it needs, the worst result first
+        let ancestors: Vec<JobResult> = self.job_ancestors(name).into_iter().map(result).collect();
+        let status = [JobResult::Failure, JobResult::Cancelled, JobResult::Skipped]
+            .into_iter()
+            .find(|worst| ancestors.contains(worst))
+            .unwrap_or(JobResult::Success);
+        let Some(condition) = &job.condition else {
+            return Ok(status == JobResult::Success);
+        };
+
+        let context = StepContext {
+            needs: self.needs_results(name, results),
+            ..context.clone()
+        };
+        let mut contexts = context.contexts(None);
+        contexts.insert("job", Value::object([("status", status.to_string())]));
+        Expression::parse_condition(condition)
+            .and_then(|condition| condition.evaluate(&contexts))
+            .map(|value| value.is_truthy())
+            .map_err(|source| Error::JobExpression {
+                job: name.to_string(),
+                source,
+            })
+    }
+
+    fn find_needs_cycle<'a>(&'a self, blocked: &[(&'a String, &'a Job)]) -> Vec<&'a str> {
+        // every blocked job waits on at least one other blocked job, so following
+        // those edges from any of them must eventually revisit a job
+        let mut path: Vec<&str> = Vec::new();
+        let mut current = blocked[0].0.as_str();
+        while !path.contains(&current) {
+            path.push(current);
+            current = self.jobs[current]
+                .needs()
+                .iter()
+                .find(|need| blocked.iter().any(|(name, _)| name == need))
+                .map(String::as_str)
+                .unwrap_or(current);
+        }
+        let start = path.iter().position(|name| *name == current).unwrap_or(0);
+        let mut cycle = path.split_off(start);
+        cycle.push(current);
+        cycle
+    }
+
+    /// Checks what GitHub checks before running a workflow and serde cannot: that
+    /// it has events and jobs, that job ids are valid, that every step either
+    /// `run`s a command or `uses` an action, and that the `needs` can be met.
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |reason: String| Err(Error::InvalidWorkflow { reason });
+        if self.on.event_names().is_empty() {
+            return invalid("it is not triggered by any event".to_string());
+        }
+        if self.jobs.is_empty() {
+            return invalid("it has no jobs".to_string());
+        }
+        for (id, job) in &self.jobs {
+            let valid_id = id.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+                && id
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+            if !valid_id {
+                return invalid(format!(
+                    "job id '{id}' must start with a letter or `_` and contain only letters, digits, `-` and `_`"
+                ));
+            }
+            if job.steps.is_empty() {
+                return invalid(format!("job '{id}' has no steps"));
+            }
+            for (index, step) in job.steps.iter().enumerate() {
+                if step.run.is_some() == step.uses.is_some() {
+                    return invalid(format!(
+                        "step {} of job '{id}' must have exactly one of `run` and `uses`",
+                        index + 1
+                    ));
+                }
+            }
+        }
+        self.jobs_in_order().map(|_| ())
+    }
+
+    /// The workflow as YAML GitHub accepts, without the keys that are not set.
+    pub fn to_yaml(&self) -> String {
+        serde_yaml::to_string(self).expect("workflows only contain string-keyed maps")
+    }
+
+    /// The permissions the `GITHUB_TOKEN` of `job` is granted.
+    pub fn effective_permissions(&self, job: &Job) -> EffectivePermissions {
+        let (source, permissions) = match (&job.permissions, &self.permissions) {
+            (Some(permissions), _) => (PermissionsSource::Job, Some(permissions)),
+            (None, Some(permissions)) => (PermissionsSource::Workflow, Some(permissions)),
+            (None, None) => (PermissionsSource::RepositoryDefault, None),
+        };
+        EffectivePermissions {
+            source,
+            permissions: permissions.cloned(),
+        }
+    }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct Push {
-    pub branches: Option<Vec<String>>,
-    pub tags: Option<Vec<String>>,
-    pub paths: Option<Vec<String>>,
+    /// The name GitHub shows for this workflow: its `name:`, or the path of the
+    /// workflow file relative to the repository root when no name is declared.
+    /// Files outside a repository are shown by the path they were loaded from.
+    pub fn display_name(&self) -> String {
+        match (&self.name, self.path.as_deref()) {
+            (Some(name), _) => name.clone(),
+            (None, Some(path)) => repository::find_root(path)
+                .zip(std::path::absolute(path).ok())
+                .and_then(|(root, path)| path.strip_prefix(root).ok().map(Path::to_path_buf))
+                .unwrap_or_else(|| path.to_path_buf())
+                .to_string_lossy()
+                .into_owned(),
+            (None, None) => String::new(),
+        }
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Job {
+    #[serde(
+        default,
+        deserialize_with = "de::one_or_many",
+        serialize_with = "de::serialize_one_or_many",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(with = "Option<de::OneOrMany>")]
+    pub needs: Option<Vec<String>>,
+    /// `if:`, the condition the job runs under once the jobs it needs are done;
+    /// `success()` when not set.
+    #[serde(
+        rename = "if",
+        default,
+        deserialize_with = "de::scalar",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(with = "Option<de::Scalar>")]
+    pub condition: Option<String>,
     #[serde(rename = "runs-on")]
-    pub runs_on: String,
+    pub runs_on: RunsOn,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub permissions: Option<Permissions>,
+    #[serde(rename = "timeout-minutes", skip_serializing_if = "Option::is_none")]
+    pub timeout_minutes: Option<Minutes>,
+    /// A failure of the job does not fail the run, and the jobs that need it
+    /// go on as if it succeeded.
+    #[serde(rename = "continue-on-error", skip_serializing_if = "Option::is_none")]
+    pub continue_on_error: Option<ContinueOnError>,
+    /// Container the steps run in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(schema_with = "container::container_schema")]
+    pub container: Option<Container>,
+    /// Service containers started next to the job, by name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(schema_with = "container::services_schema")]
+    pub services: Option<IndexMap<String, Container>>,
+    #[serde(
+        default,
+        deserialize_with = "de::scalar_map",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(with = "Option<de::ScalarMap>")]
+    pub env: Option<IndexMap<String, String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub defaults: Option<Defaults>,
     pub steps: Vec<Step>,
+    /// Where the job is declared in the workflow file.
+    #[serde(skip)]
+    pub span: Option<Span>,
 }
 
 impl Job {
+    /// Where the job is declared, if it was loaded from YAML.
+    pub fn location(&self) -> Option<Location> {
+        self.span.as_ref().map(Span::location)
+    }
+
+    /// Whether a failure of the job leaves the run going as if it succeeded.
+    pub fn continues_on_error(&self, context: &StepContext) -> bool {
+        self.continue_on_error
+            .as_ref()
+            .is_some_and(|continue_on_error| {
+                continue_on_error.holds(&context.contexts(context.env_vars.as_ref()))
+            })
+    }
+
+    fn run_defaults(&self) -> RunDefaults {
+        self.defaults
+            .as_ref()
+            .and_then(|defaults| defaults.run.clone())
+            .unwrap_or_default()
+    }
+
+    /// Names of the jobs that must complete before this one starts.
+    pub fn needs(&self) -> &[String] {
+        self.needs.as_deref().unwrap_or_default()
+    }
+
     pub fn get_step(&self, id_or_name: &str) -> Option<&Step> {
         self.steps.iter().find(|step| {
             step.name.as_deref() == Some(id_or_name) || step.id.as_deref() == Some(id_or_name)
@@ -77,15 +545,319 @@ impl Job {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// `defaults:` of a workflow or job.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct Defaults {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub run: Option<RunDefaults>,
+}
+
+/// `defaults.run`: settings applied to every `run` step that does not set them.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct RunDefaults {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shell: Option<String>,
+    #[serde(rename = "working-directory", skip_serializing_if = "Option::is_none")]
+    pub working_directory: Option<String>,
+}
+
+impl RunDefaults {
+    /// Fills the settings missing here from the less specific `fallback`.
+    pub fn or(self, fallback: RunDefaults) -> RunDefaults {
+        RunDefaults {
+            shell: self.shell.or(fallback.shell),
+            working_directory: self.working_directory.or(fallback.working_directory),
+        }
+    }
+}
+
+/// What a step inherits from the job and workflow it belongs to.
+#[derive(Debug, Clone, Default)]
+pub struct StepContext {
+    pub defaults: RunDefaults,
+    pub env_vars: Option<std::collections::HashMap<String, String>>,
+    pub secret_vars: Option<std::collections::HashMap<String, String>>,
+    /// Values of the workflow inputs, for `${{ inputs.<name> }}`.
+    pub inputs: IndexMap<String, InputValue>,
+    /// When the job the step belongs to runs out of time.
+    pub deadline: Option<Deadline>,
+    /// Set to stop the running step, e.g. on Ctrl-C.
+    pub cancelled: Arc<AtomicBool>,
+    /// Directory the steps run in, like `GITHUB_WORKSPACE` on a runner; the
+    /// current directory if not set.
+    pub workspace: Option<PathBuf>,
+    /// How the job is doing so far, for `if:` conditions.
+    pub status: JobStatus,
+    /// Properties of the `github` context known locally, e.g. `sha` and `ref`,
+    /// as found by [`repository::github_context`].
+    pub github: IndexMap<String, String>,
+    /// Results of the jobs the job needs, for `needs.<job>.result`, as given by
+    /// [`Workflow::needs_results`].
+    pub needs: IndexMap<String, JobResult>,
+}
+
+/// `job.status`, which the status functions of `if:` conditions look at.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum JobStatus {
+    #[default]
+    Success,
+    /// A step failed.
+    Failure,
+    Cancelled,
+}
+
+impl fmt::Display for JobStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            JobStatus::Success => "success",
+            JobStatus::Failure => "failure",
+            JobStatus::Cancelled => "cancelled",
+        })
+    }
+}
+
+/// `needs.<job>.result`, what became of a job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobResult {
+    Success,
+    Failure,
+    Cancelled,
+    /// Its `if:` did not hold.
+    Skipped,
+}
+
+impl fmt::Display for JobResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            JobResult::Success => "success",
+            JobResult::Failure => "failure",
+            JobResult::Cancelled => "cancelled",
+            JobResult::Skipped => "skipped",
+        })
+    }
+}
+
+impl StepContext {
+    /// The contexts expressions are evaluated against: `env` holds `env_vars`
+    /// over the variables of this process, the inputs are also
+    /// `github.event.inputs`, `github.workspace` is the workspace or the
+    /// current directory, `github` has the other properties in `github`, and
+    /// `needs` has the results in `needs`, without outputs.
+    fn contexts(&self, env_vars: Option<&std::collections::HashMap<String, String>>) -> Contexts {
+        let mut env: IndexMap<String, String> = std::env::vars().collect();
+        env.extend(
+            env_vars
+                .into_iter()
+                .flatten()
+                .map(|(k, v)| (k.clone(), v.clone())),
+        );
+        let inputs = Value::object(
+            self.inputs
+                .iter()
+                .map(|(name, value)| (name.clone(), value)),
+        );
+        let workspace = match &self.workspace {
+            Some(workspace) => workspace.clone(),
+            None => std::env::current_dir().unwrap_or_default(),
+        };
+        let mut contexts = Contexts::new();
+        contexts.insert("env", Value::object(env));
+        contexts.insert("secrets", Value::object(self.secret_vars.iter().flatten()));
+        let mut github: IndexMap<String, Value> = self
+            .github
+            .iter()
+            .map(|(name, value)| (name.clone(), Value::from(value)))
+            .collect();
+        github.insert(
+            "workspace".to_string(),
+            Value::from(workspace.display().to_string()),
+        );
+        github.insert(
+            "event".to_string(),
+            Value::object([("inputs", inputs.clone())]),
+        );
+        contexts.insert("github", Value::Object(github));
+        contexts.insert("inputs", inputs);
+        contexts.insert(
+            "needs",
+            Value::object(self.needs.iter().map(|(job, result)| {
+                (
+                    job.clone(),
+                    Value::object([
+                        ("result", Value::from(result.to_string())),
+                        ("outputs", Value::object(Vec::<(String, Value)>::new())),
+                    ]),
+                )
+            })),
+        );
+        contexts.insert("job", Value::object([("status", self.status.to_string())]));
+        contexts
+    }
+}
+
+/// `timeout-minutes` of a job or a step.
+#[derive(Debug, Clone, PartialEq, Serialize, JsonSchema)]
+#[serde(untagged)]
+pub enum Minutes {
+    Number(#[serde(serialize_with = "de::serialize_number")] f64),
+    /// An expression giving the number when the job or step starts, e.g.
+    /// `${{ inputs.timeout }}`.
+    Expression(String),
+}
+
+impl Minutes {
+    /// When these minutes run out, counting from now. An expression that does
+    /// not give a number sets no deadline, with a warning.
+    fn deadline(&self, contexts: &Contexts) -> Option<Deadline> {
+        let minutes = match self {
+            Minutes::Number(minutes) => *minutes,
+            Minutes::Expression(text) => {
+                let value = expression::interpolate(text, contexts).map_err(|err| err.to_string());
+                match value.and_then(|value| {
+                    value
+                        .trim()
+                        .parse()
+                        .map_err(|_| format!("`{value}` is not a number"))
+                }) {
+                    Ok(minutes) => minutes,
+                    Err(reason) => {
+                        warn!("Ignoring `timeout-minutes: {text}`: {reason}");
+                        return None;
+                    }
+                }
+            }
+        };
+        Some(Deadline::after_minutes(minutes))
+    }
+}
+
+impl<'de> Deserialize<'de> for Minutes {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        de::or_expression(deserializer, "a number or a ${{ }} expression")
+            .map(|minutes| minutes.map_or_else(Minutes::Expression, Minutes::Number))
+    }
+}
+
+impl From<f64> for Minutes {
+    fn from(minutes: f64) -> Self {
+        Minutes::Number(minutes)
+    }
+}
+
+impl From<&str> for Minutes {
+    fn from(expression: &str) -> Self {
+        Minutes::Expression(expression.to_string())
+    }
+}
+
+/// `continue-on-error` of a job or a step: whether its failure is let through.
+#[derive(Debug, Clone, PartialEq, Serialize, JsonSchema)]
+#[serde(untagged)]
+pub enum ContinueOnError {
+    Boolean(bool),
+    /// An expression giving `true` or `false` when the job or step runs, e.g.
+    /// `${{ matrix.experimental }}`.
+    Expression(String),
+}
+
+impl ContinueOnError {
+    /// Whether failures are let through. An expression that cannot be evaluated
+    /// does not let them through, with a warning.
+    fn holds(&self, contexts: &Contexts) -> bool {
+        match self {
+            ContinueOnError::Boolean(holds) => *holds,
+            ContinueOnError::Expression(text) => match expression::interpolate(text, contexts) {
+                Ok(value) => value.trim() == "true",
+                Err(err) => {
+                    warn!("Ignoring `continue-on-error: {text}`: {err}");
+                    false
+                }
+            },
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ContinueOnError {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        de::or_expression(deserializer, "a boolean or a ${{ }} expression")
+            .map(|holds| holds.map_or_else(ContinueOnError::Expression, ContinueOnError::Boolean))
+    }
+}
+
+impl From<bool> for ContinueOnError {
+    fn from(holds: bool) -> Self {
+        ContinueOnError::Boolean(holds)
+    }
+}
+
+/// The moment a `timeout-minutes` budget runs out.
+#[derive(Debug, Clone, Copy)]
+pub struct Deadline {
+    pub at: Instant,
+    pub minutes: f64,
+}
+
+impl Deadline {
+    pub fn after_minutes(minutes: f64) -> Self {
+        Deadline {
+            // negative and NaN budgets expire immediately
+            at: Instant::now()
+                + Duration::try_from_secs_f64(minutes.min(1e9) * 60.0).unwrap_or_default(),
+            minutes,
+        }
+    }
+}
+
+/// A step of a job. `run`, often a multi-line script, is serialized last.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct Step {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub id: Option<String>,
-    pub uses: Option<String>,
+    /// `if:`, the condition the step runs under; `success()` when not set.
+    #[serde(
+        rename = "if",
+        default,
+        deserialize_with = "de::scalar",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(with = "Option<de::Scalar>")]
+    pub condition: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uses: Option<Uses>,
+    #[serde(
+        default,
+        deserialize_with = "de::scalar_map",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(with = "Option<de::ScalarMap>")]
+    pub with: Option<IndexMap<String, String>>,
+    #[serde(
+        default,
+        deserialize_with = "de::scalar_map",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(with = "Option<de::ScalarMap>")]
+    pub env: Option<IndexMap<String, String>>,
+    #[serde(rename = "timeout-minutes", skip_serializing_if = "Option::is_none")]
+    pub timeout_minutes: Option<Minutes>,
+    /// A failure of the step does not fail the job.
+    #[serde(rename = "continue-on-error", skip_serializing_if = "Option::is_none")]
+    pub continue_on_error: Option<ContinueOnError>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub shell: Option<String>,
-    #[serde(rename = "working-directory")]
+    #[serde(rename = "working-directory", skip_serializing_if = "Option::is_none")]
     pub working_directory: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub run: Option<String>,
+    /// Where the step is declared in the workflow file.
+    #[serde(skip)]
+    pub span: Option<Span>,
 }
 
 impl Step {
@@ -95,93 +867,199 @@ impl Step {
             .unwrap_or(self.id.as_deref().unwrap_or("unknown"))
     }
 
-    fn replace_env_vars(
-        command: &str,
-        env_vars: Option<std::collections::HashMap<String, String>>,
-        secret_vars: Option<std::collections::HashMap<String, String>>,
-    ) -> String {
-        let mut result = command.to_string();
+    /// Where the step is declared, if it was loaded from YAML.
+    pub fn location(&self) -> Option<Location> {
+        self.span.as_ref().map(Span::location)
+    }
 
-        if let Some(env_vars) = env_vars {
-            let re = regex::Regex::new(r"\$\{\{\s*env\.(\w+)\s*\}\}").unwrap();
-            result = re
-                .replace_all(&result, |caps: &regex::Captures| {
-                    env_vars
-                        .get(&caps[1])
-                        .cloned()
-                        .or_else(|| std::env::var(&caps[1]).ok())
-                        .unwrap_or_else(|| "".to_string())
-                })
-                .to_string();
-        }
+    /// The step's `with:` inputs, with the expressions in their values evaluated.
+    pub fn inputs(&self, context: &StepContext) -> IndexMap<String, String> {
+        let env_vars = self.env_vars(context).unwrap_or_default();
+        self.with
+            .iter()
+            .flatten()
+            .map(|(name, value)| {
+                let value = Self::interpolate_or_keep(value, Some(&env_vars), context);
+                (name.clone(), value)
+            })
+            .collect()
+    }
 
-        if let Some(secret_vars) = secret_vars {
-            let re = regex::Regex::new(r"\$\{\{\s*secrets\.(\w+)\s*\}\}").unwrap();
-            result = re
-                .replace_all(&result, |caps: &regex::Captures| {
-                    secret_vars
-                        .get(&caps[1])
-                        .cloned()
-                        .unwrap_or_else(|| "".to_string())
-                })
-                .to_string();
-        }
+    /// The inputs as the runner hands them to an action: one `INPUT_<NAME>`
+    /// variable per input, upper-cased and with spaces replaced by underscores.
+    pub fn input_env(&self, context: &StepContext) -> IndexMap<String, String> {
+        self.inputs(context)
+            .into_iter()
+            .map(|(name, value)| {
+                (
+                    format!("INPUT_{}", name.replace(' ', "_").to_uppercase()),
+                    value,
+                )
+            })
+            .collect()
+    }
 
-        result
+    /// Whether a failure of the step leaves the job going as if it succeeded,
+    /// with `continue-on-error` evaluated in the step's own environment.
+    pub fn continues_on_error(&self, context: &StepContext) -> bool {
+        self.continue_on_error
+            .as_ref()
+            .is_some_and(|continue_on_error| {
+                continue_on_error.holds(&context.contexts(self.env_vars(context).as_ref()))
+            })
+    }
+
+    /// Environment of the step: the inherited variables overlaid with its own
+    /// `env:`.
+    fn env_vars(&self, context: &StepContext) -> Option<std::collections::HashMap<String, String>> {
+        merge_env(context.env_vars.clone(), self.env.as_ref(), context)
+    }
+
+    fn location_prefix(&self) -> String {
+        self.location()
+            .map(|location| format!("{location}: "))
+            .unwrap_or_default()
+    }
+
+    /// Whether the step runs given how its job is doing: its `if:` holds, or,
+    /// without one, no earlier step failed.
+    pub fn condition_met(&self, context: &StepContext) -> Result<bool> {
+        let Some(condition) = &self.condition else {
+            return Ok(context.status == JobStatus::Success);
+        };
+        // the step's own `env` is not set yet when its condition is evaluated
+        Expression::parse_condition(condition)
+            .and_then(|condition| condition.evaluate(&context.contexts(context.env_vars.as_ref())))
+            .map(|value| value.is_truthy())
+            .map_err(|source| Error::Expression {
+                step: self.get_name_or_id().to_string(),
+                location: self.location(),
+                source,
+            })
+    }
+
+    /// Evaluates the `${{ }}` expressions in `text`.
+    fn interpolate(
+        text: &str,
+        env_vars: Option<&std::collections::HashMap<String, String>>,
+        context: &StepContext,
+    ) -> Result<String, ExpressionError> {
+        expression::interpolate(text, &context.contexts(env_vars))
+    }
+
+    /// Like [`Step::interpolate`], for values that keep their text when it is
+    /// not a valid expression.
+    fn interpolate_or_keep(
+        text: &str,
+        env_vars: Option<&std::collections::HashMap<String, String>>,
+        context: &StepContext,
+    ) -> String {
+        Self::interpolate(text, env_vars, context).unwrap_or_else(|err| {
+            warn!("{err}");
+            text.to_string()
+        })
     }
 
     pub fn run_cmd(
         &self,
         env_vars: Option<std::collections::HashMap<String, String>>,
         secret_vars: Option<std::collections::HashMap<String, String>>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+    ) -> Result<()> {
+        self.run_in(&StepContext {
+            env_vars,
+            secret_vars,
+            ..Default::default()
+        })
+    }
+
+    /// Runs the step, resolving its shell and working directory with the
+    /// step > job > workflow precedence GitHub applies.
+    pub fn run_in(&self, context: &StepContext) -> Result<()> {
         let step_id = self.get_name_or_id();
         if self.run.is_none() {
-            if self.uses.is_none() {
-                let err = format!("No run command found for step id/name '{step_id}'");
-                error!(
-                    "{}; Step details are:\nname: {}\nid: {}\nuses: {}\nshell: {}",
-                    err,
+            if let Some(uses) = &self.uses {
+                warn!(
+                    "{}Currently, 'uses' is not supported. Skipping step '{}' (uses {})",
+                    self.location_prefix(),
+                    step_id,
+                    uses
+                );
+                for (name, value) in self.input_env(context) {
+                    debug!("Step '{step_id}' would receive {name}={value}");
+                }
+                return Ok(());
+            } else {
+                debug!(
+                    "Step details are:\nname: {}\nid: {}\nuses: NA\nshell: {}",
                     self.name.as_deref().unwrap_or("NA"),
                     self.id.as_deref().unwrap_or("NA"),
-                    self.uses.as_deref().unwrap_or("NA"),
                     self.shell.as_deref().unwrap_or("NA")
                 );
-                return Err(err.into());
-            } else {
-                warn!(
-                    "Currently, 'uses' is not supported. Skipping step '{}'",
-                    step_id
-                );
-                return Ok(());
+                return Err(Error::NothingToRun {
+                    step: step_id.to_string(),
+                    location: self.location(),
+                });
             }
         }
 
         let command = self.run.as_deref().unwrap();
-        let command = Self::replace_env_vars(command, env_vars, secret_vars)
+        let env_vars = self.env_vars(context);
+        let command = Self::interpolate(command, env_vars.as_ref(), context)
+            .map_err(|source| Error::Expression {
+                step: step_id.to_string(),
+                location: self.location(),
+                source,
+            })?
             .trim()
             .to_string();
 
-        let shell = self.shell.as_deref().unwrap_or("bash");
-        let original_dir = std::env::current_dir()?;
+        let shell = self
+            .shell
+            .as_deref()
+            .or(context.defaults.shell.as_deref())
+            .unwrap_or("bash");
+        let working_directory = self
+            .working_directory
+            .as_deref()
+            .or(context.defaults.working_directory.as_deref());
 
-        if self.working_directory.is_some() {
+        let mut cmd = Command::new(shell);
+        cmd.envs(env_vars.iter().flatten());
+        if let Some(workspace) = &context.workspace {
+            cmd.current_dir(workspace);
+        }
+        if let Some(working_directory) = working_directory {
+            let base = match &context.workspace {
+                Some(workspace) => workspace.clone(),
+                None => std::env::current_dir().unwrap_or_default(),
+            };
             info!(
                 "Changing working directory to: {}/{}",
-                original_dir.display(),
-                self.working_directory.as_deref().unwrap()
+                base.display(),
+                working_directory
             );
-            std::env::set_current_dir(self.working_directory.as_deref().unwrap())?;
+            cmd.current_dir(base.join(working_directory));
         }
 
-        info!("Running step name/id '{step_id}', using {shell} shell, with command: \n{command}\n");
+        info!(
+            "{}Running step name/id '{step_id}', using {shell} shell, with command: \n{command}\n",
+            self.location_prefix()
+        );
 
-        let mut child = Command::new(shell)
+        let spawn_error = |source| Error::Spawn {
+            step: step_id.to_string(),
+            location: self.location(),
+            shell: shell.to_string(),
+            source,
+        };
+        let mut child = cmd
             .arg("-c")
             .arg(command)
-            .stdout(std::process::Stdio::piped())
-            .stderr(std::process::Stdio::piped())
-            .spawn()?;
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(spawn_error)?;
 
         let stdout = child.stdout.take().unwrap();
         let stderr = child.stderr.take().unwrap();
@@ -202,19 +1080,86 @@ impl Step {
             }
         });
 
+        let deadline = self
+            .timeout_minutes
+            .as_ref()
+            .and_then(|minutes| minutes.deadline(&context.contexts(env_vars.as_ref())))
+            .into_iter()
+            .chain(context.deadline)
+            .min_by_key(|deadline| deadline.at);
+        let status = loop {
+            let status = child.try_wait().map_err(|source| Error::Wait {
+                step: step_id.to_string(),
+                location: self.location(),
+                source,
+            })?;
+            if let Some(status) = status {
+                break status;
+            }
+            // the output threads are left behind on purpose: processes the step
+            // started in the background may keep its pipes open indefinitely
+            if context.cancelled.load(Ordering::SeqCst) {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(Error::Cancelled {
+                    step: step_id.to_string(),
+                    location: self.location(),
+                });
+            }
+            if let Some(deadline) = deadline.filter(|deadline| Instant::now() >= deadline.at) {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(Error::Timeout {
+                    step: step_id.to_string(),
+                    location: self.location(),
+                    minutes: deadline.minutes,
+                });
+            }
+            thread::sleep(Duration::from_millis(20));
+        };
+
         stdout_thread.join().unwrap();
         stderr_thread.join().unwrap();
 
-        let status = child.wait()?;
-        std::env::set_current_dir(original_dir)?;
-
         if status.success() {
             info!("Step '{step_id}' was executed successfully");
             Ok(())
+        } else if context.cancelled.load(Ordering::SeqCst) {
+            // an interrupt reaches the step's shell at the same time as us
+            Err(Error::Cancelled {
+                step: step_id.to_string(),
+                location: self.location(),
+            })
         } else {
-            let err = format!("Step '{step_id}' failed with status: {}", status);
-            error!("{}", err);
-            Err(err.into())
+            #[cfg(unix)]
+            let signal = std::os::unix::process::ExitStatusExt::signal(&status);
+            #[cfg(not(unix))]
+            let signal = None;
+            Err(Error::Exit {
+                step: step_id.to_string(),
+                location: self.location(),
+                code: status.code(),
+                signal,
+            })
         }
     }
 }
+
+/// Layers `env` on top of the variables `inherited` from an enclosing level,
+/// evaluating the expressions in its values against the inherited ones first.
+fn merge_env(
+    inherited: Option<std::collections::HashMap<String, String>>,
+    env: Option<&IndexMap<String, String>>,
+    context: &StepContext,
+) -> Option<std::collections::HashMap<String, String>> {
+    let Some(env) = env else {
+        return inherited;
+    };
+    let inherited = inherited.unwrap_or_default();
+    let mut merged = inherited.clone();
+    for (name, value) in env {
+        let value = Step::interpolate_or_keep(value, Some(&inherited), context);
+        merged.insert(name.clone(), value);
+    }
+    Some(merged)
+}
@@ -0,0 +1,216 @@
+//! The `permissions` key of a workflow or job: what the `GITHUB_TOKEN` may do.
+
+use indexmap::IndexMap;
+use schemars::gen::SchemaGenerator;
+use schemars::schema::Schema;
+use schemars::JsonSchema;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::json;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Permissions {
+    /// `permissions: read-all`
+    ReadAll,
+    /// `permissions: write-all`
+    WriteAll,
+    /// Access per scope; scopes that are not listed get no access, so `{}`
+    /// disables every scope.
+    Scopes(IndexMap<Scope, Access>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum Scope {
+    Actions,
+    Attestations,
+    Checks,
+    Contents,
+    Deployments,
+    Discussions,
+    IdToken,
+    Issues,
+    Models,
+    Packages,
+    Pages,
+    PullRequests,
+    RepositoryProjects,
+    SecurityEvents,
+    Statuses,
+}
+
+impl Scope {
+    pub const ALL: [Scope; 15] = [
+        Scope::Actions,
+        Scope::Attestations,
+        Scope::Checks,
+        Scope::Contents,
+        Scope::Deployments,
+        Scope::Discussions,
+        Scope::IdToken,
+        Scope::Issues,
+        Scope::Models,
+        Scope::Packages,
+        Scope::Pages,
+        Scope::PullRequests,
+        Scope::RepositoryProjects,
+        Scope::SecurityEvents,
+        Scope::Statuses,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Scope::Actions => "actions",
+            Scope::Attestations => "attestations",
+            Scope::Checks => "checks",
+            Scope::Contents => "contents",
+            Scope::Deployments => "deployments",
+            Scope::Discussions => "discussions",
+            Scope::IdToken => "id-token",
+            Scope::Issues => "issues",
+            Scope::Models => "models",
+            Scope::Packages => "packages",
+            Scope::Pages => "pages",
+            Scope::PullRequests => "pull-requests",
+            Scope::RepositoryProjects => "repository-projects",
+            Scope::SecurityEvents => "security-events",
+            Scope::Statuses => "statuses",
+        }
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Access to a scope; `write` includes `read`.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Access {
+    None,
+    Read,
+    Write,
+}
+
+impl fmt::Display for Access {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Access::None => "none",
+            Access::Read => "read",
+            Access::Write => "write",
+        })
+    }
+}
+
+impl Permissions {
+    /// Access granted to `scope`.
+    pub fn access(&self, scope: Scope) -> Access {
+        match self {
+            Permissions::ReadAll => Access::Read,
+            Permissions::WriteAll => Access::Write,
+            Permissions::Scopes(scopes) => scopes.get(&scope).copied().unwrap_or(Access::None),
+        }
+    }
+
+    /// Access granted to every scope, in [`Scope::ALL`] order.
+    pub fn scopes(&self) -> IndexMap<Scope, Access> {
+        Scope::ALL
+            .into_iter()
+            .map(|scope| (scope, self.access(scope)))
+            .collect()
+    }
+}
+
+impl fmt::Display for Permissions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Permissions::ReadAll => write!(f, "read-all"),
+            Permissions::WriteAll => write!(f, "write-all"),
+            Permissions::Scopes(scopes) if scopes.is_empty() => write!(f, "{{}}"),
+            Permissions::Scopes(scopes) => {
+                let scopes: Vec<String> = scopes
+                    .iter()
+                    .map(|(scope, access)| format!("{scope}: {access}"))
+                    .collect();
+                write!(f, "{}", scopes.join(", "))
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Permissions {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = serde_yaml::Value::deserialize(deserializer)?;
+        match value.as_str() {
+            Some("read-all") => Ok(Permissions::ReadAll),
+            Some("write-all") => Ok(Permissions::WriteAll),
+            Some(other) => Err(serde::de::Error::custom(format!(
+                "invalid permissions `{other}`: expected `read-all`, `write-all` or a map of scopes to `read`, `write` or `none`"
+            ))),
+            None => IndexMap::<Scope, Access>::deserialize(value)
+                .map(Permissions::Scopes)
+                .map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+impl Serialize for Permissions {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Permissions::ReadAll => serializer.serialize_str("read-all"),
+            Permissions::WriteAll => serializer.serialize_str("write-all"),
+            Permissions::Scopes(scopes) => scopes.serialize(serializer),
+        }
+    }
+}
+
+impl JsonSchema for Permissions {
+    fn schema_name() -> String {
+        "Permissions".to_string()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        let access = json!(gen.subschema_for::<Access>());
+        let scopes: serde_json::Map<String, serde_json::Value> = Scope::ALL
+            .iter()
+            .map(|scope| (scope.as_str().to_string(), access.clone()))
+            .collect();
+        crate::de::schema(json!({
+            "oneOf": [
+                { "enum": ["read-all", "write-all"] },
+                { "type": "object", "properties": scopes, "additionalProperties": false },
+            ]
+        }))
+    }
+}
+
+/// Where the permissions a job runs with are declared.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PermissionsSource {
+    Job,
+    Workflow,
+    /// Neither the job nor the workflow set `permissions`, so the token gets the
+    /// default configured in the repository or organization settings.
+    RepositoryDefault,
+}
+
+/// The permissions of the token a job receives. A job's `permissions` replace
+/// the workflow's entirely; they are not merged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EffectivePermissions {
+    pub source: PermissionsSource,
+    /// `None` when the repository default applies, which is not known from the
+    /// workflow alone.
+    pub permissions: Option<Permissions>,
+}
+
+impl EffectivePermissions {
+    /// Access granted to every scope, if known.
+    pub fn scopes(&self) -> Option<IndexMap<Scope, Access>> {
+        self.permissions.as_ref().map(Permissions::scopes)
+    }
+}
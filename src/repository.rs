@@ -0,0 +1,190 @@
+//! The repository a workflow belongs to: where its root is and which of its
+//! files are workflows.
+
+use crate::{Error, Result, Workflow};
+use globset::GlobBuilder;
+use indexmap::IndexMap;
+use log::warn;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Where workflow files live, relative to the repository root.
+pub const WORKFLOWS_DIR: &str = ".github/workflows";
+
+/// The root of the git repository `path` is in, found the way git does: the
+/// closest of `path` and its ancestors holding `.git`, a directory in a regular
+/// checkout and a file in worktrees and submodules.
+pub fn find_root(path: impl AsRef<Path>) -> Option<PathBuf> {
+    let path = std::path::absolute(path.as_ref()).ok()?;
+    path.ancestors()
+        .find(|dir| dir.join(".git").exists())
+        .map(Path::to_path_buf)
+}
+
+/// Whether `path` is named like a workflow file, `.yml` or `.yaml`.
+pub fn is_workflow_file(path: impl AsRef<Path>) -> bool {
+    matches!(
+        path.as_ref()
+            .extension()
+            .and_then(|extension| extension.to_str()),
+        Some("yml" | "yaml")
+    )
+}
+
+/// The workflow files of the repository at `root`, sorted by name. Other files
+/// in the workflows directory, such as READMEs or editor swap files, are
+/// skipped.
+pub fn workflow_files(root: impl AsRef<Path>) -> Result<Vec<PathBuf>> {
+    let workflows_dir = root.as_ref().join(WORKFLOWS_DIR);
+    let io_error = |source| Error::Io {
+        path: workflows_dir.clone(),
+        source,
+    };
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(&workflows_dir).map_err(io_error)? {
+        let path = entry.map_err(io_error)?.path();
+        if path.is_file() && is_workflow_file(&path) {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// The workflow files `selectors` refer to, in order and without repeats; all
+/// of the repository's workflows if there are no selectors. A selector is one of:
+///
+/// - a path, from the current directory or inside the workflows directory;
+/// - a glob matched against the workflow files of the repository, e.g. `ci-*.yml`;
+/// - the `name:` of a workflow, e.g. `Continuous Integration`.
+pub fn select_workflows(root: impl AsRef<Path>, selectors: &[String]) -> Result<Vec<PathBuf>> {
+    let root = root.as_ref();
+    let workflows_dir = root.join(WORKFLOWS_DIR);
+    if selectors.is_empty() {
+        let files = workflow_files(root)?;
+        if files.is_empty() {
+            return Err(Error::WorkflowNotFound {
+                name: workflows_dir.join("*.yml").to_string_lossy().into_owned(),
+            });
+        }
+        return Ok(files);
+    }
+
+    let mut selected: Vec<PathBuf> = Vec::new();
+    for selector in selectors {
+        for path in select(root, &workflows_dir, selector)? {
+            if !selected.contains(&path) {
+                selected.push(path);
+            }
+        }
+    }
+    Ok(selected)
+}
+
+fn select(root: &Path, workflows_dir: &Path, selector: &str) -> Result<Vec<PathBuf>> {
+    let not_found = || Error::WorkflowNotFound {
+        name: selector.to_string(),
+    };
+    if let Some(path) = [PathBuf::from(selector), workflows_dir.join(selector)]
+        .into_iter()
+        .find(|path| path.is_file())
+    {
+        return Ok(vec![path]);
+    }
+    // the rest needs the repository's workflows, which may not exist
+    let files = workflow_files(root).map_err(|_| not_found())?;
+
+    // names may contain glob characters too, e.g. `CI [nightly]`
+    let glob = GlobBuilder::new(selector)
+        .literal_separator(true)
+        .build()
+        .ok()
+        .filter(|_| selector.contains(['*', '?', '[', '{']))
+        .map(|glob| glob.compile_matcher());
+    if let Some(glob) = glob {
+        let matched: Vec<PathBuf> = files
+            .iter()
+            .filter(|path| {
+                let relative = path.strip_prefix(root).unwrap_or(path);
+                path.file_name().is_some_and(|name| glob.is_match(name)) || glob.is_match(relative)
+            })
+            .cloned()
+            .collect();
+        if !matched.is_empty() {
+            return Ok(matched);
+        }
+    }
+
+    let mut named: Vec<PathBuf> = Vec::new();
+    let mut unparsed: Vec<Error> = Vec::new();
+    for path in files {
+        match Workflow::from_file(&path) {
+            Ok(workflow) if workflow.name.as_deref() == Some(selector) => named.push(path),
+            Ok(_) => {}
+            Err(err) => unparsed.push(err),
+        }
+    }
+    // the workflow asked for may be one whose name could not be read; its error
+    // is returned, and any other file that could not be parsed is warned about
+    let reported = (named.is_empty() && !unparsed.is_empty()).then(|| unparsed.remove(0));
+    for err in &unparsed {
+        warn!("Skipped a workflow that could not be parsed: {err}");
+    }
+    match named.len() {
+        0 => Err(reported.unwrap_or_else(not_found)),
+        1 => Ok(named),
+        _ => Err(Error::AmbiguousWorkflow {
+            name: selector.to_string(),
+            paths: named,
+        }),
+    }
+}
+
+/// The properties of the `github` context git can tell for the checkout at
+/// `root`: `sha`, `ref`, `ref_name` and `ref_type` of the branch checked out,
+/// and `repository` and `repository_owner` from the `origin` remote. Those git
+/// cannot tell, e.g. on a detached `HEAD`, are left out.
+pub fn github_context(root: &Path) -> IndexMap<String, String> {
+    let git = |args: &[&str]| {
+        Command::new("git")
+            .arg("-C")
+            .arg(root)
+            .args(args)
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .and_then(|output| String::from_utf8(output.stdout).ok())
+            .map(|output| output.trim().to_string())
+            .filter(|output| !output.is_empty())
+    };
+    let mut github = IndexMap::new();
+    if let Some(sha) = git(&["rev-parse", "--verify", "-q", "HEAD"]) {
+        github.insert("sha".to_string(), sha);
+    }
+    if let Some(git_ref) = git(&["symbolic-ref", "-q", "HEAD"]) {
+        if let Some(branch) = git_ref.strip_prefix("refs/heads/") {
+            github.insert("ref_name".to_string(), branch.to_string());
+            github.insert("ref_type".to_string(), "branch".to_string());
+        }
+        github.insert("ref".to_string(), git_ref);
+    }
+    if let Some(repository) = git(&["remote", "get-url", "origin"])
+        .as_deref()
+        .and_then(repository_name)
+    {
+        let owner = repository.split('/').next().unwrap_or_default();
+        github.insert("repository_owner".to_string(), owner.to_string());
+        github.insert("repository".to_string(), repository);
+    }
+    github
+}
+
+/// `owner/repo` from a remote URL such as `https://github.com/owner/repo.git`
+/// or `git@github.com:owner/repo.git`.
+fn repository_name(url: &str) -> Option<String> {
+    let path = url.trim_end_matches('/').trim_end_matches(".git");
+    let mut segments = path.rsplit(['/', ':']);
+    let repo = segments.next().filter(|segment| !segment.is_empty())?;
+    let owner = segments.next().filter(|segment| !segment.is_empty())?;
+    Some(format!("{owner}/{repo}"))
+}
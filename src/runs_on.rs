@@ -0,0 +1,145 @@
+//! The `runs-on` key of a job: which runner (or runners) may pick it up.
+
+use crate::de::OneOrMany;
+use schemars::gen::SchemaGenerator;
+use schemars::schema::Schema;
+use schemars::JsonSchema;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::json;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RunsOn {
+    /// One or more runner labels, e.g. `ubuntu-latest` or `[self-hosted, linux]`.
+    Labels(Vec<String>),
+    /// A runner group, optionally narrowed down by labels.
+    Group {
+        group: Option<String>,
+        labels: Vec<String>,
+    },
+    /// An expression only known at run time, e.g. `${{ matrix.os }}`.
+    Expression(String),
+}
+
+impl RunsOn {
+    pub fn labels(&self) -> &[String] {
+        match self {
+            RunsOn::Labels(labels) | RunsOn::Group { labels, .. } => labels,
+            RunsOn::Expression(_) => &[],
+        }
+    }
+}
+
+impl From<&str> for RunsOn {
+    fn from(label: &str) -> Self {
+        if label.trim_start().starts_with("${{") {
+            RunsOn::Expression(label.to_string())
+        } else {
+            RunsOn::Labels(vec![label.to_string()])
+        }
+    }
+}
+
+impl From<Vec<String>> for RunsOn {
+    fn from(labels: Vec<String>) -> Self {
+        RunsOn::Labels(labels)
+    }
+}
+
+impl fmt::Display for RunsOn {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RunsOn::Labels(labels) => write!(f, "{}", labels.join(", ")),
+            RunsOn::Group { group, labels } => {
+                if let Some(group) = group {
+                    write!(f, "group {group}")?;
+                    if !labels.is_empty() {
+                        write!(f, " ")?;
+                    }
+                }
+                if !labels.is_empty() {
+                    write!(f, "[{}]", labels.join(", "))?;
+                }
+                Ok(())
+            }
+            RunsOn::Expression(expression) => write!(f, "{expression}"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum RawRunsOn {
+    One(String),
+    Many(Vec<String>),
+    Group {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        group: Option<String>,
+        #[serde(
+            default,
+            deserialize_with = "crate::de::one_or_many",
+            skip_serializing_if = "Option::is_none"
+        )]
+        labels: Option<Vec<String>>,
+    },
+}
+
+impl<'de> Deserialize<'de> for RunsOn {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = serde_yaml::Value::deserialize(deserializer)?;
+        let raw = RawRunsOn::deserialize(value).map_err(|_| {
+            serde::de::Error::custom(
+                "invalid runs-on: expected a label, a list of labels, a map with `group` and/or `labels`, or an expression",
+            )
+        })?;
+        Ok(match raw {
+            RawRunsOn::One(value) if value.trim_start().starts_with("${{") => {
+                RunsOn::Expression(value)
+            }
+            RawRunsOn::One(label) => RunsOn::Labels(vec![label]),
+            RawRunsOn::Many(labels) => RunsOn::Labels(labels),
+            RawRunsOn::Group { group, labels } => RunsOn::Group {
+                group,
+                labels: labels.unwrap_or_default(),
+            },
+        })
+    }
+}
+
+impl Serialize for RunsOn {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let raw = match self {
+            RunsOn::Labels(labels) if labels.len() == 1 => RawRunsOn::One(labels[0].clone()),
+            RunsOn::Labels(labels) => RawRunsOn::Many(labels.clone()),
+            RunsOn::Group { group, labels } => RawRunsOn::Group {
+                group: group.clone(),
+                labels: (!labels.is_empty()).then(|| labels.clone()),
+            },
+            RunsOn::Expression(expression) => RawRunsOn::One(expression.clone()),
+        };
+        raw.serialize(serializer)
+    }
+}
+
+impl JsonSchema for RunsOn {
+    fn schema_name() -> String {
+        "RunsOn".to_string()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        crate::de::schema(json!({
+            "oneOf": [
+                { "type": "string" },
+                { "type": "array", "items": { "type": "string" } },
+                {
+                    "type": "object",
+                    "properties": {
+                        "group": { "type": "string" },
+                        "labels": gen.subschema_for::<OneOrMany>(),
+                    },
+                    "additionalProperties": false,
+                },
+            ]
+        }))
+    }
+}
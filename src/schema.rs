@@ -0,0 +1,23 @@
+//! JSON Schemas generated from the types files are read into, so that editors
+//! and hooks accept exactly what actionoscope parses.
+
+use crate::{Action, Workflow};
+use schemars::gen::SchemaSettings;
+use schemars::JsonSchema;
+
+fn root_schema<T: JsonSchema>() -> serde_json::Value {
+    let schema = SchemaSettings::draft07()
+        .into_generator()
+        .into_root_schema_for::<T>();
+    serde_json::to_value(schema).expect("schemas serialize to JSON")
+}
+
+/// The JSON Schema (draft 7) of a workflow file.
+pub fn workflow() -> serde_json::Value {
+    root_schema::<Workflow>()
+}
+
+/// The JSON Schema (draft 7) of an action's `action.yml`.
+pub fn action() -> serde_json::Value {
+    root_schema::<Action>()
+}
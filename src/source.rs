@@ -0,0 +1,588 @@
+//! Where things live in a workflow file.
+//!
+//! serde_yaml does not expose positions for the values it deserializes, so the
+//! document is parsed a second time into a lightweight node tree that remembers
+//! the span of every key and value. The tree is then flattened into a
+//! [`SourceMap`] keyed by the same dotted paths serde uses in its error messages
+//! (`jobs.build.steps[2].run`). Aliases and merge keys are resolved in the tree,
+//! so a value brought in from an anchor keeps the position it was written at.
+
+use crate::strict::UnknownKey;
+use saphyr_parser::{Event, Marker, Parser, ScalarStyle, SpannedEventReceiver};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+
+/// A point in a workflow file, as shown to users (`ci.yml:42:9`).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Location {
+    pub file: Option<PathBuf>,
+    /// 1-based line number.
+    pub line: usize,
+    /// 1-based column number, counted in characters.
+    pub column: usize,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{}:", file.display())?;
+        }
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// A position inside a file, without the file itself.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Position {
+    /// 1-based line number.
+    pub line: usize,
+    /// 1-based column number, counted in characters.
+    pub column: usize,
+    /// Byte offset from the start of the file.
+    pub offset: usize,
+}
+
+/// The region of a workflow file a key or value was parsed from.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct Span {
+    pub file: Option<PathBuf>,
+    pub start: Position,
+    pub end: Position,
+}
+
+impl Span {
+    pub fn location(&self) -> Location {
+        Location {
+            file: self.file.clone(),
+            line: self.start.line,
+            column: self.start.column,
+        }
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.location().fmt(f)
+    }
+}
+
+impl fmt::Debug for Span {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Span({self}..{}:{})", self.end.line, self.end.column)
+    }
+}
+
+/// A workflow that could not be parsed, and where the problem is.
+#[derive(Debug)]
+pub struct ParseError {
+    pub location: Option<Location>,
+    pub message: String,
+    /// Every key rejected in strict mode; empty for any other kind of error.
+    pub unknown_keys: Vec<UnknownKey>,
+}
+
+impl ParseError {
+    pub(crate) fn from_yaml(err: serde_yaml::Error, file: Option<PathBuf>) -> Self {
+        let mut message = err.to_string();
+        let location = err.location().map(|location| {
+            // serde_yaml appends the position to its message; it is shown in front
+            // of the message instead
+            let suffix = format!(" at line {} column {}", location.line(), location.column());
+            if let Some(stripped) = message.strip_suffix(&suffix) {
+                message = stripped.to_string();
+            }
+            Location {
+                file: file.clone(),
+                line: location.line(),
+                column: location.column(),
+            }
+        });
+        let location = location.or_else(|| {
+            file.map(|file| Location {
+                file: Some(file),
+                line: 1,
+                column: 1,
+            })
+        });
+        ParseError {
+            location,
+            message,
+            unknown_keys: Vec::new(),
+        }
+    }
+
+    /// An error found while deserializing the document, located through the path
+    /// of the value it is about.
+    pub(crate) fn from_path_error(
+        err: serde_path_to_error::Error<serde_yaml::Error>,
+        source_map: &SourceMap,
+    ) -> Self {
+        let path = err.path().to_string();
+        let err = err.into_inner();
+        let message = if path == "." {
+            err.to_string()
+        } else {
+            format!("{path}: {err}")
+        };
+        // the path may end in segments serde could not name, or in a value the
+        // source map does not know; the closest enclosing value is used instead
+        let mut location = None;
+        let mut prefix = path.as_str();
+        while location.is_none() && !prefix.is_empty() {
+            location = source_map.value(prefix).map(Span::location);
+            prefix = &prefix[..prefix.rfind(['.', '[']).unwrap_or(0)];
+        }
+        let location = location.or_else(|| {
+            source_map.file().map(|file| Location {
+                file: Some(file.clone()),
+                line: 1,
+                column: 1,
+            })
+        });
+        ParseError {
+            location,
+            message,
+            unknown_keys: Vec::new(),
+        }
+    }
+
+    /// A document whose aliases expand to more values than [`MAX_ALIAS_EXPANSION`],
+    /// located at the alias that crossed the limit.
+    pub(crate) fn from_alias_limit(position: Position, file: Option<PathBuf>) -> Self {
+        ParseError {
+            location: Some(Location {
+                file,
+                line: position.line,
+                column: position.column,
+            }),
+            message: format!("aliases expand to more than {MAX_ALIAS_EXPANSION} values"),
+            unknown_keys: Vec::new(),
+        }
+    }
+
+    pub(crate) fn from_unknown_keys(unknown_keys: Vec<UnknownKey>) -> Self {
+        ParseError {
+            location: unknown_keys.first().and_then(|key| key.location.clone()),
+            message: format!("{} unknown key(s)", unknown_keys.len()),
+            unknown_keys,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.unknown_keys.is_empty() {
+            let lines: Vec<String> = self
+                .unknown_keys
+                .iter()
+                .map(|key| key.to_string())
+                .collect();
+            return write!(f, "{}", lines.join("\n"));
+        }
+        match &self.location {
+            Some(location) => write!(f, "{location}: {}", self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// The spans of a single entry of the document: the key it is stored under (if
+/// it is a map entry) and its value.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Entry {
+    pub key: Option<Span>,
+    pub value: Span,
+}
+
+/// Spans of every key and value of a workflow file, by path.
+#[derive(Clone, Default)]
+pub struct SourceMap {
+    file: Option<PathBuf>,
+    entries: HashMap<String, Entry>,
+}
+
+impl fmt::Debug for SourceMap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SourceMap")
+            .field("file", &self.file)
+            .field("entries", &self.entries.len())
+            .finish()
+    }
+}
+
+impl SourceMap {
+    /// Builds the source map of `yaml_data`. Documents the span parser cannot read
+    /// yield an empty map: positions are a convenience and never a reason to
+    /// reject a workflow serde accepted.
+    pub fn parse(yaml_data: &str, file: Option<PathBuf>) -> Self {
+        let root = Node::parse(yaml_data, None).ok().flatten();
+        Self::from_node(root.as_ref(), file)
+    }
+
+    pub(crate) fn from_node(root: Option<&Node>, file: Option<PathBuf>) -> Self {
+        let mut source_map = SourceMap {
+            file,
+            entries: HashMap::new(),
+        };
+        if let Some(root) = root {
+            source_map.insert(String::new(), None, root);
+        }
+        source_map
+    }
+
+    pub fn file(&self) -> Option<&PathBuf> {
+        self.file.as_ref()
+    }
+
+    pub fn entry(&self, path: &str) -> Option<&Entry> {
+        self.entries.get(path)
+    }
+
+    /// Span of the value stored at `path`.
+    pub fn value(&self, path: &str) -> Option<&Span> {
+        self.entry(path).map(|entry| &entry.value)
+    }
+
+    /// Span of the key `path` is stored under, falling back to its value for
+    /// sequence items.
+    pub fn key(&self, path: &str) -> Option<&Span> {
+        self.entry(path)
+            .map(|entry| entry.key.as_ref().unwrap_or(&entry.value))
+    }
+
+    fn span(&self, node: &Node) -> Span {
+        Span {
+            file: self.file.clone(),
+            start: node.start,
+            end: node.end,
+        }
+    }
+
+    fn insert(&mut self, path: String, key: Option<&Node>, node: &Node) {
+        let entry = Entry {
+            key: key.map(|key| self.span(key)),
+            value: self.span(node),
+        };
+        match &node.kind {
+            NodeKind::Scalar(_) => {}
+            NodeKind::Sequence(items) => {
+                for (index, item) in items.iter().enumerate() {
+                    self.insert(format!("{path}[{index}]"), None, item);
+                }
+            }
+            NodeKind::Mapping(entries) => {
+                for (key, value) in entries {
+                    let name = key.as_str().unwrap_or_default();
+                    let child = if path.is_empty() {
+                        name.to_string()
+                    } else {
+                        format!("{path}.{name}")
+                    };
+                    self.insert(child, Some(key), value);
+                }
+            }
+        }
+        self.entries.insert(path, entry);
+    }
+}
+
+/// A YAML node with its position, aliases already replaced by the node they
+/// refer to and merge keys already applied.
+#[derive(Debug, Clone)]
+pub(crate) struct Node {
+    pub kind: NodeKind,
+    pub start: Position,
+    pub end: Position,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum NodeKind {
+    Scalar(String),
+    Sequence(Vec<Node>),
+    Mapping(Vec<(Node, Node)>),
+}
+
+/// Most values aliases may bring into a document. Every alias is expanded into a
+/// copy of its anchor, so a few nested anchors can otherwise grow into billions
+/// of values.
+pub(crate) const MAX_ALIAS_EXPANSION: usize = 100_000;
+
+impl Node {
+    /// The tree of `yaml_data`, or `None` when the span parser cannot read it.
+    /// Fails only when aliases expand past [`MAX_ALIAS_EXPANSION`].
+    pub fn parse(yaml_data: &str, file: Option<PathBuf>) -> Result<Option<Node>, ParseError> {
+        let mut builder = TreeBuilder {
+            source: yaml_data,
+            offsets: yaml_data
+                .char_indices()
+                .map(|(offset, _)| offset)
+                .chain(std::iter::once(yaml_data.len()))
+                .collect(),
+            stack: Vec::new(),
+            anchors: HashMap::new(),
+            expanded: 0,
+            too_many_aliases: None,
+            root: None,
+        };
+        if Parser::new_from_str(yaml_data)
+            .load(&mut builder, false)
+            .is_err()
+        {
+            return Ok(None);
+        }
+        match builder.too_many_aliases {
+            Some(position) => Err(ParseError::from_alias_limit(position, file)),
+            None => Ok(builder.root),
+        }
+    }
+
+    /// Number of values in this node, itself included.
+    fn size(&self) -> usize {
+        1 + match &self.kind {
+            NodeKind::Scalar(_) => 0,
+            NodeKind::Sequence(items) => items.iter().map(Node::size).sum(),
+            NodeKind::Mapping(entries) => entries
+                .iter()
+                .map(|(key, value)| key.size() + value.size())
+                .sum(),
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match &self.kind {
+            NodeKind::Scalar(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// Resolves the `<<` merge keys of a mapping the way serde_yaml does: keys
+/// written in the mapping win over merged ones, and earlier merged mappings win
+/// over later ones. Merged entries keep the spans of the mapping they come from.
+fn merge_keys(entries: Vec<(Node, Node)>) -> Vec<(Node, Node)> {
+    if !entries.iter().any(|(key, _)| key.as_str() == Some("<<")) {
+        return entries;
+    }
+    let mut merged = Vec::with_capacity(entries.len());
+    let mut inherited = Vec::new();
+    for (key, value) in entries {
+        if key.as_str() != Some("<<") {
+            merged.push((key, value));
+            continue;
+        }
+        match value.kind {
+            NodeKind::Mapping(entries) => inherited.extend(entries),
+            NodeKind::Sequence(items) => {
+                for item in items {
+                    if let NodeKind::Mapping(entries) = item.kind {
+                        inherited.extend(entries);
+                    }
+                }
+            }
+            NodeKind::Scalar(_) => {}
+        }
+    }
+    for (key, value) in inherited {
+        let name = key.as_str();
+        if name.is_none() || !merged.iter().any(|(existing, _)| existing.as_str() == name) {
+            merged.push((key, value));
+        }
+    }
+    merged
+}
+
+enum Frame {
+    Sequence {
+        start: Position,
+        anchor: usize,
+        items: Vec<Node>,
+    },
+    Mapping {
+        start: Position,
+        anchor: usize,
+        entries: Vec<(Node, Node)>,
+        key: Option<Node>,
+    },
+}
+
+struct TreeBuilder<'a> {
+    source: &'a str,
+    /// Byte offset of every character, as the parser counts positions in chars.
+    offsets: Vec<usize>,
+    stack: Vec<Frame>,
+    /// Anchored nodes, with their size.
+    anchors: HashMap<usize, (Node, usize)>,
+    /// Values brought in by aliases so far.
+    expanded: usize,
+    /// Where aliases first expanded past [`MAX_ALIAS_EXPANSION`].
+    too_many_aliases: Option<Position>,
+    root: Option<Node>,
+}
+
+impl TreeBuilder<'_> {
+    /// Where a scalar really ends. The parser ends quoted and block scalars where
+    /// the next token starts, after any trailing spaces, comments and blank lines.
+    fn scalar_end(&self, style: ScalarStyle, start: Position, end: Position) -> Position {
+        let text = &self.source[start.offset..end.offset.max(start.offset)];
+        let length = match style {
+            ScalarStyle::SingleQuoted => {
+                let mut chars = text.char_indices().skip(1).peekable();
+                let mut length = text.len();
+                while let Some((index, c)) = chars.next() {
+                    if c == '\'' {
+                        if chars.peek().map(|(_, next)| *next) == Some('\'') {
+                            chars.next();
+                            continue;
+                        }
+                        length = index + 1;
+                        break;
+                    }
+                }
+                length
+            }
+            ScalarStyle::DoubleQuoted => {
+                let mut chars = text.char_indices().skip(1);
+                let mut length = text.len();
+                while let Some((index, c)) = chars.next() {
+                    match c {
+                        '\\' => {
+                            chars.next();
+                        }
+                        '"' => {
+                            length = index + 1;
+                            break;
+                        }
+                        _ => {}
+                    }
+                }
+                length
+            }
+            ScalarStyle::Literal | ScalarStyle::Folded => text.trim_end().len(),
+            _ => return end,
+        };
+        let skipped = &text[..length];
+        let line = start.line + skipped.matches('\n').count();
+        let column = match skipped.rfind('\n') {
+            Some(newline) => skipped[newline + 1..].chars().count() + 1,
+            None => start.column + skipped.chars().count(),
+        };
+        Position {
+            line,
+            column,
+            offset: start.offset + length,
+        }
+    }
+
+    fn position(&self, marker: Marker) -> Position {
+        Position {
+            line: marker.line(),
+            column: marker.col() + 1,
+            offset: self
+                .offsets
+                .get(marker.index())
+                .copied()
+                .unwrap_or_else(|| self.offsets.last().copied().unwrap_or_default()),
+        }
+    }
+
+    fn push(&mut self, node: Node, anchor: usize) {
+        if anchor != 0 {
+            self.anchors.insert(anchor, (node.clone(), node.size()));
+        }
+        match self.stack.last_mut() {
+            Some(Frame::Sequence { items, .. }) => items.push(node),
+            Some(Frame::Mapping { entries, key, .. }) => match key.take() {
+                Some(key) => entries.push((key, node)),
+                None => *key = Some(node),
+            },
+            None => self.root = Some(node),
+        }
+    }
+}
+
+impl<'input> SpannedEventReceiver<'input> for TreeBuilder<'_> {
+    fn on_event(&mut self, event: Event<'input>, span: saphyr_parser::Span) {
+        let start = self.position(span.start);
+        let end = self.position(span.end);
+        match event {
+            Event::Scalar(value, style, anchor, _) => {
+                let node = Node {
+                    kind: NodeKind::Scalar(value.into_owned()),
+                    start,
+                    end: self.scalar_end(style, start, end),
+                };
+                self.push(node, anchor);
+            }
+            Event::Alias(anchor) => {
+                if self.too_many_aliases.is_some() {
+                    return;
+                }
+                if let Some((node, size)) = self.anchors.get(&anchor) {
+                    self.expanded += size;
+                    if self.expanded > MAX_ALIAS_EXPANSION {
+                        self.too_many_aliases = Some(start);
+                        return;
+                    }
+                    let node = node.clone();
+                    self.push(node, 0);
+                }
+            }
+            Event::SequenceStart(anchor, _) => self.stack.push(Frame::Sequence {
+                start,
+                anchor,
+                items: Vec::new(),
+            }),
+            Event::MappingStart(anchor, _) => self.stack.push(Frame::Mapping {
+                start,
+                anchor,
+                entries: Vec::new(),
+                key: None,
+            }),
+            Event::SequenceEnd | Event::MappingEnd => {
+                let (kind, node_start, anchor, last_end) = match self.stack.pop() {
+                    Some(Frame::Sequence {
+                        start,
+                        anchor,
+                        items,
+                    }) => {
+                        let last_end = items.last().map(|item| item.end);
+                        (NodeKind::Sequence(items), start, anchor, last_end)
+                    }
+                    Some(Frame::Mapping {
+                        start,
+                        anchor,
+                        entries,
+                        ..
+                    }) => {
+                        let last_end = entries.last().map(|(_, value)| value.end);
+                        (
+                            NodeKind::Mapping(merge_keys(entries)),
+                            start,
+                            anchor,
+                            last_end,
+                        )
+                    }
+                    None => return,
+                };
+                // block collections end where the next token starts, possibly
+                // several lines and comments later; flow collections end at
+                // their closing bracket
+                let node_end = if span.start.index() != span.end.index() {
+                    end
+                } else {
+                    last_end.unwrap_or(node_start)
+                };
+                let node = Node {
+                    kind,
+                    start: node_start,
+                    end: node_end,
+                };
+                self.push(node, anchor);
+            }
+            _ => {}
+        }
+    }
+}
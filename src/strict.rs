@@ -0,0 +1,296 @@
+//! Strict parsing: every key GitHub accepts at each level of a workflow, used to
+//! report the keys serde would otherwise silently ignore.
+
+use crate::source::{Location, Node, NodeKind, SourceMap};
+use std::fmt;
+
+/// A key that GitHub would not accept where it was written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownKey {
+    pub location: Option<Location>,
+    /// Path of the mapping the key was found in, e.g. `jobs.build.steps[0]`.
+    pub parent: String,
+    pub key: String,
+    /// The closest key that is accepted at the same level, if any is close enough.
+    pub suggestion: Option<String>,
+}
+
+impl fmt::Display for UnknownKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(location) = &self.location {
+            write!(f, "{location}: ")?;
+        }
+        write!(f, "unknown key `{}`", self.key)?;
+        if !self.parent.is_empty() {
+            write!(f, " in {}", self.parent)?;
+        }
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, ", did you mean `{suggestion}`?")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Shape {
+    /// Free-form content (`env`, `with`, `matrix` values, ...).
+    Any,
+    /// A mapping that only accepts the listed keys.
+    Object(&'static [(&'static str, Shape)]),
+    /// A mapping with arbitrary keys whose values all have the same shape.
+    MapOf(&'static Shape),
+    /// A sequence whose items all have the same shape.
+    ListOf(&'static Shape),
+}
+
+const ACTIVITY: Shape = Shape::Object(&[("types", Shape::Any)]);
+const UNFILTERED: Shape = Shape::Object(&[]);
+
+const PUSH: Shape = Shape::Object(&[
+    ("branches", Shape::Any),
+    ("branches-ignore", Shape::Any),
+    ("tags", Shape::Any),
+    ("tags-ignore", Shape::Any),
+    ("paths", Shape::Any),
+    ("paths-ignore", Shape::Any),
+]);
+
+const PULL_REQUEST: Shape = Shape::Object(&[
+    ("types", Shape::Any),
+    ("branches", Shape::Any),
+    ("branches-ignore", Shape::Any),
+    ("paths", Shape::Any),
+    ("paths-ignore", Shape::Any),
+]);
+
+const WORKFLOW_RUN: Shape = Shape::Object(&[
+    ("workflows", Shape::Any),
+    ("types", Shape::Any),
+    ("branches", Shape::Any),
+    ("branches-ignore", Shape::Any),
+]);
+
+const SCHEDULE: Shape = Shape::Object(&[("cron", Shape::Any)]);
+
+const DISPATCH_INPUT: Shape = Shape::Object(&[
+    ("description", Shape::Any),
+    ("required", Shape::Any),
+    ("default", Shape::Any),
+    ("type", Shape::Any),
+    ("options", Shape::Any),
+    ("deprecationMessage", Shape::Any),
+]);
+
+const WORKFLOW_DISPATCH: Shape = Shape::Object(&[("inputs", Shape::MapOf(&DISPATCH_INPUT))]);
+
+const CALL_INPUT: Shape = Shape::Object(&[
+    ("description", Shape::Any),
+    ("required", Shape::Any),
+    ("default", Shape::Any),
+    ("type", Shape::Any),
+    ("deprecationMessage", Shape::Any),
+]);
+
+const CALL_OUTPUT: Shape = Shape::Object(&[("description", Shape::Any), ("value", Shape::Any)]);
+
+const CALL_SECRET: Shape = Shape::Object(&[("description", Shape::Any), ("required", Shape::Any)]);
+
+const WORKFLOW_CALL: Shape = Shape::Object(&[
+    ("inputs", Shape::MapOf(&CALL_INPUT)),
+    ("outputs", Shape::MapOf(&CALL_OUTPUT)),
+    ("secrets", Shape::MapOf(&CALL_SECRET)),
+]);
+
+const EVENTS: Shape = Shape::Object(&[
+    ("branch_protection_rule", ACTIVITY),
+    ("check_run", ACTIVITY),
+    ("check_suite", ACTIVITY),
+    ("create", UNFILTERED),
+    ("delete", UNFILTERED),
+    ("deployment", UNFILTERED),
+    ("deployment_status", UNFILTERED),
+    ("discussion", ACTIVITY),
+    ("discussion_comment", ACTIVITY),
+    ("fork", UNFILTERED),
+    ("gollum", UNFILTERED),
+    ("issue_comment", ACTIVITY),
+    ("issues", ACTIVITY),
+    ("label", ACTIVITY),
+    ("merge_group", ACTIVITY),
+    ("milestone", ACTIVITY),
+    ("page_build", UNFILTERED),
+    ("project", ACTIVITY),
+    ("project_card", ACTIVITY),
+    ("project_column", ACTIVITY),
+    ("public", UNFILTERED),
+    ("pull_request", PULL_REQUEST),
+    ("pull_request_review", ACTIVITY),
+    ("pull_request_review_comment", ACTIVITY),
+    ("pull_request_target", PULL_REQUEST),
+    ("push", PUSH),
+    ("registry_package", ACTIVITY),
+    ("release", ACTIVITY),
+    ("repository_dispatch", ACTIVITY),
+    ("schedule", Shape::ListOf(&SCHEDULE)),
+    ("status", UNFILTERED),
+    ("watch", ACTIVITY),
+    ("workflow_call", WORKFLOW_CALL),
+    ("workflow_dispatch", WORKFLOW_DISPATCH),
+    ("workflow_run", WORKFLOW_RUN),
+]);
+
+const PERMISSIONS: Shape = Shape::Object(&[
+    ("actions", Shape::Any),
+    ("attestations", Shape::Any),
+    ("checks", Shape::Any),
+    ("contents", Shape::Any),
+    ("deployments", Shape::Any),
+    ("discussions", Shape::Any),
+    ("id-token", Shape::Any),
+    ("issues", Shape::Any),
+    ("models", Shape::Any),
+    ("packages", Shape::Any),
+    ("pages", Shape::Any),
+    ("pull-requests", Shape::Any),
+    ("repository-projects", Shape::Any),
+    ("security-events", Shape::Any),
+    ("statuses", Shape::Any),
+]);
+
+const RUN_DEFAULTS: Shape =
+    Shape::Object(&[("shell", Shape::Any), ("working-directory", Shape::Any)]);
+
+const DEFAULTS: Shape = Shape::Object(&[("run", RUN_DEFAULTS)]);
+
+const CONCURRENCY: Shape =
+    Shape::Object(&[("group", Shape::Any), ("cancel-in-progress", Shape::Any)]);
+
+const ENVIRONMENT: Shape = Shape::Object(&[("name", Shape::Any), ("url", Shape::Any)]);
+
+const STRATEGY: Shape = Shape::Object(&[
+    ("matrix", Shape::Any),
+    ("fail-fast", Shape::Any),
+    ("max-parallel", Shape::Any),
+]);
+
+const CREDENTIALS: Shape = Shape::Object(&[("username", Shape::Any), ("password", Shape::Any)]);
+
+const CONTAINER: Shape = Shape::Object(&[
+    ("image", Shape::Any),
+    ("credentials", CREDENTIALS),
+    ("env", Shape::Any),
+    ("ports", Shape::Any),
+    ("volumes", Shape::Any),
+    ("options", Shape::Any),
+]);
+
+const RUNS_ON: Shape = Shape::Object(&[("group", Shape::Any), ("labels", Shape::Any)]);
+
+const STEP: Shape = Shape::Object(&[
+    ("id", Shape::Any),
+    ("if", Shape::Any),
+    ("name", Shape::Any),
+    ("uses", Shape::Any),
+    ("run", Shape::Any),
+    ("working-directory", Shape::Any),
+    ("shell", Shape::Any),
+    ("with", Shape::Any),
+    ("env", Shape::Any),
+    ("continue-on-error", Shape::Any),
+    ("timeout-minutes", Shape::Any),
+]);
+
+const JOB: Shape = Shape::Object(&[
+    ("name", Shape::Any),
+    ("permissions", PERMISSIONS),
+    ("needs", Shape::Any),
+    ("if", Shape::Any),
+    ("runs-on", RUNS_ON),
+    ("environment", ENVIRONMENT),
+    ("concurrency", CONCURRENCY),
+    ("outputs", Shape::Any),
+    ("env", Shape::Any),
+    ("defaults", DEFAULTS),
+    ("steps", Shape::ListOf(&STEP)),
+    ("timeout-minutes", Shape::Any),
+    ("strategy", STRATEGY),
+    ("continue-on-error", Shape::Any),
+    ("container", CONTAINER),
+    ("services", Shape::MapOf(&CONTAINER)),
+    ("uses", Shape::Any),
+    ("with", Shape::Any),
+    ("secrets", Shape::Any),
+]);
+
+const WORKFLOW: Shape = Shape::Object(&[
+    ("name", Shape::Any),
+    ("run-name", Shape::Any),
+    ("on", EVENTS),
+    ("permissions", PERMISSIONS),
+    ("env", Shape::Any),
+    ("defaults", DEFAULTS),
+    ("concurrency", CONCURRENCY),
+    ("jobs", Shape::MapOf(&JOB)),
+]);
+
+/// Every key of the workflow document that GitHub does not accept at the level
+/// it was written at, in document order.
+pub(crate) fn unknown_keys(root: &Node, source_map: &SourceMap) -> Vec<UnknownKey> {
+    let mut unknown = Vec::new();
+    check(root, "", WORKFLOW, source_map, &mut unknown);
+    unknown
+}
+
+fn check(node: &Node, path: &str, shape: Shape, source_map: &SourceMap, out: &mut Vec<UnknownKey>) {
+    match (shape, &node.kind) {
+        (Shape::Object(keys), NodeKind::Mapping(entries)) => {
+            for (key, value) in entries {
+                let name = key.as_str().unwrap_or_default();
+                let child = child_path(path, name);
+                match keys.iter().find(|(known, _)| *known == name) {
+                    Some((_, shape)) => check(value, &child, *shape, source_map, out),
+                    None => out.push(UnknownKey {
+                        location: source_map.key(&child).map(|span| span.location()),
+                        parent: path.to_string(),
+                        key: name.to_string(),
+                        suggestion: suggest(name, keys.iter().map(|(known, _)| *known)),
+                    }),
+                }
+            }
+        }
+        (Shape::MapOf(shape), NodeKind::Mapping(entries)) => {
+            for (key, value) in entries {
+                let child = child_path(path, key.as_str().unwrap_or_default());
+                check(value, &child, *shape, source_map, out);
+            }
+        }
+        (Shape::ListOf(shape), NodeKind::Sequence(items)) => {
+            for (index, item) in items.iter().enumerate() {
+                check(item, &format!("{path}[{index}]"), *shape, source_map, out);
+            }
+        }
+        // scalar shorthands (`permissions: read-all`, `container: node:20`, ...) and
+        // free-form values have no keys to check
+        _ => {}
+    }
+}
+
+fn child_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{path}.{key}")
+    }
+}
+
+fn suggest<'a>(key: &str, candidates: impl Iterator<Item = &'a str>) -> Option<String> {
+    // `working_directory` and `Runs-On` are the most common slips, so separators
+    // and case are ignored before measuring the distance
+    let normalize = |value: &str| value.to_lowercase().replace('_', "-");
+    let key = normalize(key);
+    candidates
+        .map(|candidate| (strsim::levenshtein(&key, &normalize(candidate)), candidate))
+        .filter(|(distance, candidate)| *distance <= (candidate.len() / 3).max(1))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate.to_string())
+}
@@ -0,0 +1,208 @@
+//! The `uses` key of a step: which action (or workflow, or image) it runs.
+
+use schemars::gen::SchemaGenerator;
+use schemars::schema::Schema;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Uses {
+    /// An action or workflow in the same repository, e.g.
+    /// `./.github/actions/prepare-environment`.
+    Local { path: String },
+    /// An action published in a repository, e.g. `actions/checkout@v4` or
+    /// `github/codeql-action/init@<sha>`.
+    Repository {
+        owner: String,
+        repo: String,
+        /// Directory of the action inside the repository, if not at its root.
+        path: Option<String>,
+        git_ref: String,
+    },
+    /// A reusable workflow, e.g. `octo-org/ci/.github/workflows/build.yml@main`.
+    ReusableWorkflow {
+        owner: String,
+        repo: String,
+        path: String,
+        git_ref: String,
+    },
+    /// A container image from a registry, e.g. `docker://alpine:3.19`.
+    Docker { image: String },
+}
+
+impl Uses {
+    /// The tag, branch or commit SHA a remote reference is pinned to.
+    pub fn git_ref(&self) -> Option<&str> {
+        match self {
+            Uses::Repository { git_ref, .. } | Uses::ReusableWorkflow { git_ref, .. } => {
+                Some(git_ref)
+            }
+            Uses::Local { .. } | Uses::Docker { .. } => None,
+        }
+    }
+
+    /// Whether the reference is pinned to a full-length commit SHA, the only ref
+    /// that cannot be moved after the fact.
+    pub fn is_pinned_to_sha(&self) -> bool {
+        self.git_ref().is_some_and(|git_ref| {
+            git_ref.len() == 40 && git_ref.chars().all(|c| c.is_ascii_hexdigit())
+        })
+    }
+
+    /// Whether this references a reusable workflow rather than an action.
+    pub fn is_workflow(&self) -> bool {
+        match self {
+            Uses::ReusableWorkflow { .. } => true,
+            Uses::Local { path } => is_workflow_path(path),
+            _ => false,
+        }
+    }
+}
+
+pub(crate) fn is_workflow_path(path: &str) -> bool {
+    let path = path.trim_start_matches("./");
+    path.starts_with(".github/workflows/") && (path.ends_with(".yml") || path.ends_with(".yaml"))
+}
+
+/// A `uses` value that does not reference anything GitHub could resolve.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UsesError {
+    pub value: String,
+    pub reason: String,
+}
+
+impl fmt::Display for UsesError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid uses `{}`: {}", self.value, self.reason)
+    }
+}
+
+impl std::error::Error for UsesError {}
+
+impl FromStr for Uses {
+    type Err = UsesError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: &str| UsesError {
+            value: value.to_string(),
+            reason: reason.to_string(),
+        };
+
+        if value.trim().is_empty() {
+            return Err(invalid("it is empty"));
+        }
+        if value.trim() != value {
+            return Err(invalid("it has leading or trailing whitespace"));
+        }
+        if let Some(image) = value.strip_prefix("docker://") {
+            if image.is_empty() {
+                return Err(invalid("the image name is missing after `docker://`"));
+            }
+            return Ok(Uses::Docker {
+                image: image.to_string(),
+            });
+        }
+        if value.starts_with("./") {
+            return Ok(Uses::Local {
+                path: value.to_string(),
+            });
+        }
+        if value.starts_with('/') || value.starts_with("../") || value == "." {
+            return Err(invalid(
+                "local actions are referenced from the repository root and start with `./`",
+            ));
+        }
+
+        let Some((target, git_ref)) = value.split_once('@') else {
+            return Err(invalid(
+                "a ref is required, e.g. `actions/checkout@v4` or `owner/repo@<commit sha>`",
+            ));
+        };
+        if git_ref.is_empty() {
+            return Err(invalid("the ref after `@` is empty"));
+        }
+        let mut segments = target.split('/');
+        let owner = segments.next().unwrap_or_default();
+        let repo = segments.next().unwrap_or_default();
+        let path: Vec<&str> = segments.collect();
+        if owner.is_empty() || repo.is_empty() {
+            return Err(invalid("expected `<owner>/<repo>[/<path>]@<ref>`"));
+        }
+        if path.iter().any(|segment| segment.is_empty()) {
+            return Err(invalid(
+                "the path inside the repository has an empty segment",
+            ));
+        }
+        let path = (!path.is_empty()).then(|| path.join("/"));
+
+        Ok(match path {
+            Some(path) if is_workflow_path(&path) => Uses::ReusableWorkflow {
+                owner: owner.to_string(),
+                repo: repo.to_string(),
+                path,
+                git_ref: git_ref.to_string(),
+            },
+            path => Uses::Repository {
+                owner: owner.to_string(),
+                repo: repo.to_string(),
+                path,
+                git_ref: git_ref.to_string(),
+            },
+        })
+    }
+}
+
+impl JsonSchema for Uses {
+    fn schema_name() -> String {
+        "Uses".to_string()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        crate::de::schema(json!({
+            "type": "string",
+            "pattern": r"^(docker://.+|\./.*|[^/@]+/[^/@]+(/[^/@]+)*@.+)$",
+        }))
+    }
+}
+
+impl TryFrom<String> for Uses {
+    type Error = UsesError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Uses> for String {
+    fn from(uses: Uses) -> Self {
+        uses.to_string()
+    }
+}
+
+impl fmt::Display for Uses {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Uses::Local { path } => write!(f, "{path}"),
+            Uses::Repository {
+                owner,
+                repo,
+                path,
+                git_ref,
+            } => match path {
+                Some(path) => write!(f, "{owner}/{repo}/{path}@{git_ref}"),
+                None => write!(f, "{owner}/{repo}@{git_ref}"),
+            },
+            Uses::ReusableWorkflow {
+                owner,
+                repo,
+                path,
+                git_ref,
+            } => write!(f, "{owner}/{repo}/{path}@{git_ref}"),
+            Uses::Docker { image } => write!(f, "docker://{image}"),
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use actionoscope::{schema, Action, Error, Runs, Workflow};
+
+#[test]
+fn test_load_the_repository_action() {
+    let action = Action::from_file(".github/actions/prepare-environment").unwrap();
+    assert_eq!(action.name, "Prepare environment");
+    assert!(action
+        .path
+        .unwrap()
+        .ends_with(".github/actions/prepare-environment/action.yaml"));
+    let Runs::Composite { steps } = &action.runs else {
+        panic!("expected a composite action, got {:?}", action.runs);
+    };
+    assert_eq!(steps.len(), 4);
+    assert_eq!(
+        steps[2].run.as_deref(),
+        Some("rustup component add rustfmt")
+    );
+    assert_eq!(steps[0].location().unwrap().line, 7);
+}
+
+#[test]
+fn test_inputs_and_outputs() {
+    let yaml_data = r#"
+name: Random number
+inputs:
+  digits:
+    description: How many digits
+    required: true
+    default: 3
+  seed:
+    deprecationMessage: Use `digits` instead
+outputs:
+  number:
+    description: The number
+    value: ${{ steps.random.outputs.number }}
+runs:
+  using: composite
+  steps:
+    - id: random
+      shell: bash
+      run: echo "number=$RANDOM" >> $GITHUB_OUTPUT
+"#;
+    let action = Action::from_yaml(yaml_data).expect("Failed to parse YAML");
+    let inputs = action.inputs.as_ref().unwrap();
+    assert!(inputs["digits"].required);
+    assert_eq!(inputs["digits"].default.as_deref(), Some("3"));
+    assert!(!inputs["seed"].required);
+    assert_eq!(
+        inputs["seed"].deprecation_message.as_deref(),
+        Some("Use `digits` instead")
+    );
+    assert_eq!(
+        action.outputs.as_ref().unwrap()["number"].value.as_deref(),
+        Some("${{ steps.random.outputs.number }}")
+    );
+}
+
+#[test]
+fn test_node_and_docker_actions() {
+    let node = Action::from_yaml(
+        "name: Hello\nruns:\n  using: node20\n  main: dist/index.js\n  post: dist/cleanup.js\n  post-if: success()\n",
+    )
+    .unwrap();
+    let Runs::Node {
+        using,
+        main,
+        post_if,
+        ..
+    } = &node.runs
+    else {
+        panic!("expected a node action, got {:?}", node.runs);
+    };
+    assert_eq!((using.as_str(), main.as_str()), ("node20", "dist/index.js"));
+    assert_eq!(post_if.as_deref(), Some("success()"));
+
+    let docker = Action::from_yaml(
+        "name: Lint\nruns:\n  using: docker\n  image: docker://alpine:3.19\n  args: [--strict]\n  env:\n    LEVEL: 2\n",
+    )
+    .unwrap();
+    let Runs::Docker {
+        image, args, env, ..
+    } = &docker.runs
+    else {
+        panic!("expected a docker action, got {:?}", docker.runs);
+    };
+    assert_eq!(image, "docker://alpine:3.19");
+    assert_eq!(args, &["--strict"]);
+    assert_eq!(env.as_ref().unwrap()["LEVEL"], "2");
+}
+
+#[test]
+fn test_invalid_runs() {
+    let err = Action::from_yaml("name: Hello\nruns:\n  using: node20\n").unwrap_err();
+    assert!(
+        err.message
+            .contains("`main` is required with `using: node20`"),
+        "{err}"
+    );
+    let err = Action::from_yaml("name: Hello\nruns:\n  using: python\n  main: a.py\n").unwrap_err();
+    assert!(err.message.contains("unsupported `using: python`"), "{err}");
+    assert_eq!(err.location.unwrap().line, 3);
+}
+
+#[test]
+fn test_discover_local_actions() {
+    let yaml_data = r#"
+on: push
+jobs:
+  build:
+    runs-on: ubuntu-latest
+    steps:
+      - uses: actions/checkout@v4
+      - uses: ./.github/actions/prepare-environment
+      - uses: ./.github/actions/missing
+"#;
+    let workflow = Workflow::from_yaml(yaml_data).unwrap();
+    let steps = &workflow.get_job("build").unwrap().steps;
+    assert!(steps[0].local_action(".").unwrap().is_none());
+    let action = steps[1].local_action(".").unwrap().unwrap();
+    assert_eq!(action.name, "Prepare environment");
+    assert!(matches!(
+        steps[2].local_action("."),
+        Err(Error::ActionNotFound { .. })
+    ));
+
+    let instance: serde_json::Value =
+        serde_yaml::from_str(&std::fs::read_to_string(action.path.unwrap()).unwrap()).unwrap();
+    assert!(jsonschema::is_valid(&schema::action(), &instance));
+}
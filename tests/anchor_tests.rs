@@ -0,0 +1,98 @@
+use actionoscope::{ParseOptions, Workflow};
+
+const WORKFLOW: &str = r#"on: push
+env: &common_env
+  CARGO_TERM_COLOR: always
+  RUST_LOG: info
+jobs:
+  build:
+    runs-on: ubuntu-latest
+    env:
+      <<: *common_env
+      RUST_LOG: debug
+    steps: &checkout_and_build
+      - uses: actions/checkout@v4
+      - name: Build
+        run: cargo build
+  test:
+    runs-on: ubuntu-latest
+    steps: *checkout_and_build
+"#;
+
+#[test]
+fn test_alias_shares_step_list() {
+    let workflow = Workflow::from_yaml(WORKFLOW).expect("Failed to parse YAML");
+    let test = workflow.get_job("test").unwrap();
+    assert_eq!(test.steps.len(), 2);
+    assert_eq!(test.steps[1].run.as_deref(), Some("cargo build"));
+}
+
+#[test]
+fn test_merge_key_with_override() {
+    let workflow = Workflow::from_yaml(WORKFLOW).expect("Failed to parse YAML");
+    let env = workflow.get_job("build").unwrap().env.as_ref().unwrap();
+    assert_eq!(
+        env.get("CARGO_TERM_COLOR").map(String::as_str),
+        Some("always")
+    );
+    assert_eq!(env.get("RUST_LOG").map(String::as_str), Some("debug"));
+    assert!(!env.contains_key("<<"));
+}
+
+#[test]
+fn test_aliased_values_keep_their_original_location() {
+    let workflow = Workflow::from_yaml(WORKFLOW).expect("Failed to parse YAML");
+    let test = workflow.get_job("test").unwrap();
+    assert_eq!(test.steps[1].location().unwrap().to_string(), "13:9");
+    let merged = workflow
+        .source_map
+        .key("jobs.build.env.CARGO_TERM_COLOR")
+        .unwrap();
+    assert_eq!((merged.start.line, merged.start.column), (3, 3));
+}
+
+#[test]
+fn test_error_in_merged_value_points_at_anchor() {
+    let yaml_data = r#"on: push
+x-defaults: &job_defaults
+  runs-on: ubuntu-latest
+  timeout-minutes: soon
+jobs:
+  build:
+    <<: *job_defaults
+    steps:
+      - run: cargo build
+"#;
+    let err = Workflow::from_yaml(yaml_data).unwrap_err();
+    assert!(err
+        .message
+        .starts_with("jobs.build.timeout-minutes: invalid type"));
+    let location = err.location.expect("error has no location");
+    assert_eq!((location.line, location.column), (4, 20));
+}
+
+#[test]
+fn test_strict_mode_accepts_merge_keys() {
+    let options = ParseOptions { strict: true };
+    assert!(Workflow::from_yaml_with_options(WORKFLOW, &options).is_ok());
+}
+
+#[test]
+fn test_alias_bomb_is_rejected() {
+    let mut yaml_data =
+        String::from("on: push\nx-0: &a0 [lol, lol, lol, lol, lol, lol, lol, lol, lol]\n");
+    for level in 1..10 {
+        let previous = format!("*a{}", level - 1);
+        yaml_data.push_str(&format!(
+            "x-{level}: &a{level} [{}]\n",
+            vec![previous; 9].join(", ")
+        ));
+    }
+    yaml_data.push_str(
+        "jobs:\n  build:\n    runs-on: ubuntu-latest\n    steps:\n      - run: echo lol\n",
+    );
+    let err = Workflow::from_yaml(&yaml_data).unwrap_err();
+    assert_eq!(err.message, "aliases expand to more than 100000 values");
+    let location = err.location.expect("error has no location");
+    assert_eq!(location.line, 7);
+}
@@ -0,0 +1,157 @@
+use actionoscope::events::Push;
+use actionoscope::{Error, Job, Permissions, Step, Trigger, Workflow};
+
+fn service_workflow(service: &str) -> Workflow {
+    Workflow::builder()
+        .name(format!("Deploy {service}"))
+        .on(Trigger {
+            push: Some(Push {
+                branches: Some(vec!["main".to_string()]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .permissions(Permissions::ReadAll)
+        .env("SERVICE", service)
+        .job(
+            "build",
+            Job::builder("ubuntu-latest")
+                .step(
+                    Step::builder()
+                        .uses("actions/checkout@v4".parse().unwrap())
+                        .with("fetch-depth", "0")
+                        .build(),
+                )
+                .step(
+                    Step::builder()
+                        .name("Build")
+                        .run("cargo build --release\ncargo test")
+                        .build(),
+                )
+                .build(),
+        )
+        .job(
+            "deploy",
+            Job::builder("ubuntu-latest")
+                .needs("build")
+                .timeout_minutes(10.0)
+                .step(Step::builder().name("Deploy").run("./deploy.sh").build())
+                .build(),
+        )
+        .build()
+        .expect("Failed to build workflow")
+}
+
+#[test]
+fn test_to_yaml_is_clean() {
+    assert_eq!(
+        service_workflow("billing").to_yaml(),
+        r#"name: Deploy billing
+on:
+  push:
+    branches:
+    - main
+permissions: read-all
+env:
+  SERVICE: billing
+jobs:
+  build:
+    runs-on: ubuntu-latest
+    steps:
+    - uses: actions/checkout@v4
+      with:
+        fetch-depth: '0'
+    - name: Build
+      run: |-
+        cargo build --release
+        cargo test
+  deploy:
+    needs: build
+    runs-on: ubuntu-latest
+    timeout-minutes: 10
+    steps:
+    - name: Deploy
+      run: ./deploy.sh
+"#
+    );
+}
+
+#[test]
+fn test_to_yaml_round_trips() {
+    let workflow = service_workflow("billing");
+    let parsed = Workflow::from_yaml(&workflow.to_yaml()).expect("Failed to parse YAML");
+    assert_eq!(parsed.to_yaml(), workflow.to_yaml());
+    assert_eq!(parsed.get_job("deploy").unwrap().needs(), ["build"]);
+    assert_eq!(
+        parsed.get_job("build").unwrap().steps[0]
+            .with
+            .as_ref()
+            .unwrap()["fetch-depth"],
+        "0"
+    );
+}
+
+#[test]
+fn test_parsed_workflow_serializes_without_nulls() {
+    let yaml_data = r#"
+on: [push, workflow_dispatch]
+jobs:
+  test:
+    runs-on: [self-hosted, linux]
+    steps:
+      - run: cargo test
+"#;
+    let workflow = Workflow::from_yaml(yaml_data).expect("Failed to parse YAML");
+    let yaml = workflow.to_yaml();
+    assert!(!yaml.contains("null"), "{yaml}");
+    assert!(yaml.contains("runs-on:\n    - self-hosted\n    - linux\n"));
+}
+
+#[test]
+fn test_build_rejects_invalid_workflows() {
+    let step = || Step::builder().run("true").build();
+    let on_push = || Trigger {
+        push: Some(Default::default()),
+        ..Default::default()
+    };
+    let cases = [
+        (
+            Workflow::builder().job("a", Job::builder("ubuntu-latest").step(step()).build()),
+            "it is not triggered by any event",
+        ),
+        (Workflow::builder().on(on_push()), "it has no jobs"),
+        (
+            Workflow::builder()
+                .on(on_push())
+                .job("1st", Job::builder("ubuntu-latest").step(step()).build()),
+            "job id '1st' must start with a letter",
+        ),
+        (
+            Workflow::builder().on(on_push()).job(
+                "a",
+                Job::builder("ubuntu-latest").step(Step::default()).build(),
+            ),
+            "step 1 of job 'a' must have exactly one of `run` and `uses`",
+        ),
+    ];
+    for (builder, reason) in cases {
+        match builder.build() {
+            Err(Error::InvalidWorkflow { reason: got }) => {
+                assert!(got.starts_with(reason), "{got}")
+            }
+            other => panic!("expected an invalid workflow error, got {other:?}"),
+        }
+    }
+
+    let missing_need = Workflow::builder().on(on_push()).job(
+        "a",
+        Job::builder("ubuntu-latest")
+            .needs("b")
+            .step(step())
+            .build(),
+    );
+    assert!(matches!(
+        missing_need.build(),
+        Err(Error::MissingNeed { .. })
+    ));
+}
@@ -1,3 +1,5 @@
+mod common;
+
 use assert_cmd::Command;
 use predicates::prelude::*;
 use std::fs;
@@ -29,7 +31,7 @@ fn test_run_single_step_invalid_job() {
         .arg("--step")
         .arg("step1");
 
-    cmd.assert().failure();
+    cmd.assert().failure().code(6);
 }
 
 #[test]
@@ -43,7 +45,10 @@ fn test_run_single_step_invalid_step() {
         .arg("--step")
         .arg("invalid_step");
 
-    cmd.assert().failure();
+    cmd.assert()
+        .failure()
+        .code(7)
+        .stdout(predicate::str::contains("Step 'invalid_step' not found"));
 }
 
 #[test]
@@ -109,3 +114,124 @@ fn setup_test_workflow() {
 fn init() {
     setup_test_workflow();
 }
+
+#[test]
+fn test_ls_strict_reports_unknown_keys() {
+    let workflow_content = r#"
+    on: push
+    jobs:
+      test_job:
+        runs-on: ubuntu-latest
+        steps:
+          - name: Step 1
+            working_directory: src
+            run: echo "Step 1"
+    "#;
+    let scratch = common::scratch_dir();
+    let path = scratch.path().join("strict_workflow.yml");
+    fs::write(&path, workflow_content).unwrap();
+
+    let mut cmd = Command::cargo_bin("actionoscope").unwrap();
+    cmd.arg("ls")
+        .arg("--strict")
+        .arg("--workflow-file")
+        .arg(&path);
+
+    cmd.assert().failure().stdout(predicate::str::contains(
+        "did you mean `working-directory`?",
+    ));
+}
+
+#[test]
+fn test_run_missing_workflow_exit_code() {
+    let mut cmd = Command::cargo_bin("actionoscope").unwrap();
+    cmd.arg("run")
+        .arg("--workflow-file")
+        .arg("missing_workflow.yml");
+
+    cmd.assert()
+        .failure()
+        .code(4)
+        .stdout(predicate::str::contains(
+            "missing_workflow.yml was not found",
+        ));
+}
+
+#[test]
+fn test_run_with_invalid_input() {
+    let workflow_content = r#"
+    on:
+      workflow_dispatch:
+        inputs:
+          level:
+            type: choice
+            options: [low, high]
+    jobs:
+      test_job:
+        runs-on: ubuntu-latest
+        steps:
+          - run: echo "level ${{ inputs.level }}"
+    "#;
+    let scratch = common::scratch_dir();
+    let path = scratch.path().join("inputs_workflow.yml");
+    fs::write(&path, workflow_content).unwrap();
+
+    let mut cmd = Command::cargo_bin("actionoscope").unwrap();
+    cmd.arg("run")
+        .arg("--workflow-file")
+        .arg(&path)
+        .arg("--input")
+        .arg("level=high");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("[cmd]: level high"));
+
+    let mut cmd = Command::cargo_bin("actionoscope").unwrap();
+    cmd.arg("run")
+        .arg("--workflow-file")
+        .arg(&path)
+        .arg("--input")
+        .arg("level=medium");
+    cmd.assert()
+        .failure()
+        .code(10)
+        .stdout(predicate::str::contains(
+            "Invalid input 'level': expected one of low, high",
+        ));
+}
+
+#[test]
+fn test_permissions_command() {
+    let workflow_content = r#"
+    on: push
+    permissions: read-all
+    jobs:
+      build:
+        runs-on: ubuntu-latest
+        steps:
+          - run: cargo build
+      publish:
+        runs-on: ubuntu-latest
+        permissions:
+          packages: write
+        steps:
+          - run: cargo publish
+    "#;
+    let scratch = common::scratch_dir();
+    let path = scratch.path().join("permissions_workflow.yml");
+    fs::write(&path, workflow_content).unwrap();
+
+    let mut cmd = Command::cargo_bin("actionoscope").unwrap();
+    cmd.arg("permissions")
+        .arg("--workflow-file")
+        .arg(&path)
+        .arg("--job")
+        .arg("publish");
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "publish: set by the job\n    packages: write\n",
+        ))
+        .stdout(predicate::str::contains("build:").not());
+}
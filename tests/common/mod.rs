@@ -0,0 +1,84 @@
+//! Helpers shared by the integration tests. Each test crate uses only some of
+//! them.
+#![allow(dead_code)]
+
+use actionoscope::expression::{self, Contexts, Value};
+use std::fs;
+use tempfile::TempDir;
+
+/// A directory of the calling test's own, removed when it is dropped.
+pub fn scratch_dir() -> TempDir {
+    tempfile::Builder::new()
+        .prefix("actionoscope")
+        .tempdir()
+        .unwrap()
+}
+
+/// A scratch directory that is the root of a repository.
+pub fn scratch_repository() -> TempDir {
+    let dir = scratch_dir();
+    fs::create_dir_all(dir.path().join(".git")).unwrap();
+    dir
+}
+
+/// Contexts the expression tests evaluate against.
+pub fn contexts() -> Contexts {
+    let mut contexts = Contexts::new();
+    contexts.insert(
+        "env",
+        Value::object([
+            ("Region", "eu-west-1"),
+            ("EMPTY", ""),
+            (
+                "MATRIX",
+                r#"{"os": ["ubuntu-latest", "windows-latest"], "debug": true}"#,
+            ),
+        ]),
+    );
+    contexts.insert(
+        "github",
+        Value::object([
+            (
+                "sha",
+                Value::from("ffac537e6cbbf934b08745a378932722df287a53"),
+            ),
+            ("ref", Value::from("refs/heads/Release-1.2")),
+            ("event_name", Value::from("push")),
+            (
+                "event",
+                Value::object([
+                    (
+                        "commits",
+                        Value::from(vec![
+                            Value::object([("message", "first")]),
+                            Value::object([("message", "second")]),
+                        ]),
+                    ),
+                    (
+                        "issue",
+                        Value::object([(
+                            "labels",
+                            Value::from(vec![
+                                Value::object([("name", "bug")]),
+                                Value::object([("name", "Help Wanted")]),
+                            ]),
+                        )]),
+                    ),
+                ]),
+            ),
+        ]),
+    );
+    contexts.insert(
+        "needs",
+        Value::object([
+            ("build", Value::object([("result", "success")])),
+            ("lint", Value::object([("result", "failure")])),
+        ]),
+    );
+    contexts
+}
+
+/// Evaluates `source` against [`contexts`], which must succeed.
+pub fn eval(source: &str) -> Value {
+    expression::evaluate(source, &contexts()).unwrap()
+}
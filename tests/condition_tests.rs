@@ -0,0 +1,222 @@
+mod common;
+
+use actionoscope::expression::Expression;
+use actionoscope::{Error, JobStatus, Step, StepContext, Workflow};
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+
+fn met(condition: Option<&str>, status: JobStatus) -> bool {
+    let mut step = Step::builder().run("true");
+    if let Some(condition) = condition {
+        step = step.condition(condition);
+    }
+    let context = StepContext {
+        env_vars: Some(std::collections::HashMap::from([(
+            String::from("DEPLOY"),
+            String::from("yes"),
+        )])),
+        status,
+        ..Default::default()
+    };
+    step.build().condition_met(&context).unwrap()
+}
+
+#[test]
+fn test_status_functions() {
+    use JobStatus::*;
+    for (condition, expected) in [
+        (None, [true, false, false]),
+        (Some("success()"), [true, false, false]),
+        (Some("failure()"), [false, true, false]),
+        (Some("always()"), [true, true, true]),
+        (Some("cancelled()"), [false, false, true]),
+        (Some("${{ always() }}"), [true, true, true]),
+        (Some("!cancelled()"), [true, true, false]),
+        (Some("failure() || cancelled()"), [false, true, true]),
+    ] {
+        for (status, expected) in [Success, Failure, Cancelled].into_iter().zip(expected) {
+            assert_eq!(met(condition, status), expected, "{condition:?} {status}");
+        }
+    }
+}
+
+#[test]
+fn test_conditions_without_status_function_need_success() {
+    assert!(met(Some("env.DEPLOY == 'yes'"), JobStatus::Success));
+    assert!(met(Some("${{ env.DEPLOY == 'yes' }}"), JobStatus::Success));
+    assert!(!met(Some("env.DEPLOY == 'yes'"), JobStatus::Failure));
+    assert!(!met(Some("env.DEPLOY == 'no'"), JobStatus::Success));
+    assert!(met(Some("always() && env.DEPLOY"), JobStatus::Failure));
+    assert!(!met(
+        Some("contains(job.status, 'fail')"),
+        JobStatus::Success
+    ));
+    assert!(met(Some("true"), JobStatus::Success));
+    assert!(!met(Some("false"), JobStatus::Success));
+
+    // the `${{ }}` only comes off when it wraps the whole condition
+    assert_eq!(
+        Expression::parse_condition(" ${{ '}}' == '}}' }} ")
+            .unwrap()
+            .as_str(),
+        "'}}' == '}}'"
+    );
+    assert!(Expression::parse_condition("${{ 1 }} == ${{ 1 }}").is_err());
+}
+
+#[test]
+fn test_if_is_parsed_and_checked() {
+    let workflow = Workflow::from_yaml(
+        r#"
+on: push
+jobs:
+  build:
+    runs-on: ubuntu-latest
+    steps:
+      - run: cargo build
+      - name: Notify
+        if: ${{ failure() }}
+        run: echo failed
+      - name: Broken
+        if: success() &&
+        run: echo never
+"#,
+    )
+    .unwrap();
+    let steps = &workflow.get_job("build").unwrap().steps;
+    assert_eq!(steps[0].condition, None);
+    assert_eq!(steps[1].condition.as_deref(), Some("${{ failure() }}"));
+    assert!(workflow.to_yaml().contains("if: ${{ failure() }}"));
+
+    let err = steps[2].condition_met(&StepContext::default()).unwrap_err();
+    assert!(matches!(err, Error::Expression { .. }));
+    assert!(err
+        .to_string()
+        .contains("Step 'Broken' has an invalid expression: Unexpected end of expression"));
+}
+
+#[test]
+fn test_job_goes_on_after_a_failure() {
+    let scratch = common::scratch_repository();
+    let root = scratch.path();
+    let workflow = root.join("ci.yml");
+    fs::write(
+        &workflow,
+        r#"on: push
+jobs:
+  test:
+    runs-on: ubuntu-latest
+    steps:
+      - name: Test
+        run: echo testing && exit 3
+      - name: Lint
+        run: echo linting
+      - name: Report
+        if: failure()
+        run: echo reporting
+      - name: Clean up
+        if: ${{ always() }}
+        run: echo cleaning up
+      - name: Deploy
+        if: github.event_name == ''
+        run: echo deploying
+"#,
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("actionoscope").unwrap();
+    cmd.arg("run")
+        .arg("--repo")
+        .arg(root)
+        .arg("-w")
+        .arg(&workflow);
+    // logged when it happens, not again when the run ends
+    let once = || {
+        predicate::function(|out: &str| {
+            out.matches("Step 'Test' failed with exit code 3").count() == 1
+        })
+    };
+    cmd.assert()
+        .code(1)
+        .stdout(once())
+        .stdout(predicate::str::contains("[cmd]: testing"))
+        .stdout(predicate::str::contains(
+            "Skipping step 'Lint' because an earlier step failed",
+        ))
+        .stdout(predicate::str::contains("[cmd]: linting").not())
+        .stdout(predicate::str::contains("[cmd]: reporting"))
+        .stdout(predicate::str::contains("[cmd]: cleaning up"))
+        .stdout(predicate::str::contains(
+            "Skipping step 'Deploy': its condition `github.event_name == ''` is false",
+        ))
+        .stdout(predicate::str::contains(
+            "Step 'Test' failed with exit code 3",
+        ));
+
+    let mut cmd = Command::cargo_bin("actionoscope").unwrap();
+    cmd.arg("run")
+        .arg("--repo")
+        .arg(root)
+        .arg("-w")
+        .arg(&workflow)
+        .arg("-s")
+        .arg("Test");
+    cmd.assert().code(1).stdout(once());
+}
+
+#[test]
+fn test_continue_on_error() {
+    let scratch = common::scratch_repository();
+    let root = scratch.path();
+    let path = root.join("ci.yml");
+    fs::write(
+        &path,
+        r#"on: push
+jobs:
+  experimental:
+    runs-on: ubuntu-latest
+    continue-on-error: true
+    steps:
+      - name: Nightly
+        run: exit 4
+  test:
+    needs: experimental
+    runs-on: ubuntu-latest
+    steps:
+      - name: Flaky
+        continue-on-error: ${{ env.FLAKY == 'yes' }}
+        env:
+          FLAKY: yes
+        run: echo flaky && exit 2
+      - name: Test
+        run: echo testing
+      - name: Report
+        if: failure()
+        run: echo reporting
+"#,
+    )
+    .unwrap();
+    let workflow = Workflow::from_file(&path).unwrap();
+    let test = workflow.get_job("test").unwrap();
+    assert!(test.steps[0].continues_on_error(&StepContext::default()));
+    assert!(!test.steps[1].continues_on_error(&StepContext::default()));
+    assert!(workflow.to_yaml().contains("continue-on-error: true"));
+    assert!(Workflow::from_yaml(
+        "on: push\njobs:\n  a:\n    runs-on: x\n    continue-on-error: maybe\n    steps: []\n"
+    )
+    .is_err());
+
+    let mut cmd = Command::cargo_bin("actionoscope").unwrap();
+    cmd.arg("run").arg("--repo").arg(root).arg("-w").arg(&path);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Job 'experimental' failed, continuing as it has `continue-on-error`",
+        ))
+        .stdout(predicate::str::contains(
+            "Step 'Flaky' failed with exit code 2, continuing as the step has `continue-on-error`",
+        ))
+        .stdout(predicate::str::contains("[cmd]: testing"))
+        .stdout(predicate::str::contains("[cmd]: reporting").not());
+}
@@ -0,0 +1,129 @@
+mod common;
+
+use actionoscope::{Container, Credentials, Job, LocalLimitation, Workflow};
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+const WORKFLOW: &str = r#"
+on: push
+jobs:
+  test:
+    runs-on: ubuntu-latest
+    container:
+      image: ghcr.io/acme/builder:1.2
+      credentials:
+        username: ${{ github.actor }}
+        password: ${{ secrets.GITHUB_TOKEN }}
+      env:
+        NODE_ENV: test
+      ports: [80, "8443:443"]
+      volumes:
+        - my_docker_volume:/volume_mount
+      options: --cpus 1
+    services:
+      db:
+        image: postgres:15
+        ports:
+          - 5432:5432
+        env:
+          POSTGRES_PASSWORD: postgres
+      cache: redis:7
+    steps:
+      - run: echo "Test step"
+"#;
+
+#[test]
+fn test_container_and_services_are_parsed() {
+    let workflow = Workflow::from_yaml(WORKFLOW).expect("Failed to parse YAML");
+    let job = workflow.get_job("test").unwrap();
+    let container = job.container.as_ref().unwrap();
+    assert_eq!(container.image, "ghcr.io/acme/builder:1.2");
+    assert_eq!(
+        container.credentials,
+        Some(Credentials {
+            username: "${{ github.actor }}".to_string(),
+            password: "${{ secrets.GITHUB_TOKEN }}".to_string(),
+        })
+    );
+    assert_eq!(container.env.as_ref().unwrap()["NODE_ENV"], "test");
+    assert_eq!(container.ports, ["80", "8443:443"]);
+    assert_eq!(container.volumes, ["my_docker_volume:/volume_mount"]);
+    assert_eq!(container.options.as_deref(), Some("--cpus 1"));
+
+    let services = job.services.as_ref().unwrap();
+    assert_eq!(services["db"].ports, ["5432:5432"]);
+    assert_eq!(services["cache"], Container::new("redis:7"));
+}
+
+#[test]
+fn test_local_limitations() {
+    let workflow = Workflow::from_yaml(WORKFLOW).expect("Failed to parse YAML");
+    let limitations = workflow.get_job("test").unwrap().local_limitations();
+    assert_eq!(
+        limitations[0],
+        LocalLimitation::Container {
+            image: "ghcr.io/acme/builder:1.2".to_string(),
+            ignored: vec!["env", "volumes", "options", "credentials"],
+        }
+    );
+    assert!(limitations[0]
+        .to_string()
+        .ends_with("; ignored locally: `env`, `volumes`, `options`, `credentials`"));
+    assert_eq!(
+        limitations[1].to_string(),
+        "the service `db` (`postgres:15`) will not be started, steps that use it need it running locally (ports 5432:5432); ignored locally: `env`"
+    );
+    assert_eq!(
+        limitations[2].to_string(),
+        "the service `cache` (`redis:7`) will not be started, steps that use it need it running locally"
+    );
+    assert_eq!(limitations.len(), 3);
+    assert!(Job::builder("ubuntu-latest")
+        .build()
+        .local_limitations()
+        .is_empty());
+}
+
+#[test]
+fn test_container_image_shorthand_round_trips() {
+    let job = Job::builder("ubuntu-latest")
+        .container(Container::new("node:20"))
+        .service(
+            "db",
+            Container {
+                ports: vec!["5432".to_string()],
+                ..Container::new("postgres:15")
+            },
+        )
+        .build();
+    let yaml = serde_yaml::to_string(&job).unwrap();
+    assert!(yaml.contains("container: node:20\n"), "{yaml}");
+    assert!(
+        yaml.contains("services:\n  db:\n    image: postgres:15\n    ports:\n    - '5432'\n"),
+        "{yaml}"
+    );
+}
+
+#[test]
+fn test_invalid_container() {
+    let yaml_data = WORKFLOW.replace("      image: ghcr.io/acme/builder:1.2\n", "");
+    let err = Workflow::from_yaml(&yaml_data).unwrap_err();
+    assert!(err.message.contains("missing field `image`"), "{err}");
+    assert_eq!(err.location.unwrap().line, 7);
+}
+
+#[test]
+fn test_run_reports_limitations_before_running() {
+    let scratch = common::scratch_dir();
+    let path = scratch.path().join("container_workflow.yml");
+    std::fs::write(&path, WORKFLOW).unwrap();
+
+    let mut cmd = Command::cargo_bin("actionoscope").unwrap();
+    cmd.arg("run").arg("--workflow-file").arg(&path);
+    cmd.assert().success().stdout(
+        predicate::str::is_match(
+            "(?s)Job 'test' cannot be fully reproduced locally:.*the job runs in the container `ghcr.io/acme/builder:1.2`.*the service `cache` \\(`redis:7`\\).*Running job 'test'.*Test step",
+        )
+        .unwrap(),
+    );
+}
@@ -0,0 +1,171 @@
+use actionoscope::{Document, EnvTarget, Error, Step};
+
+const WORKFLOW: &str = r#"# Continuous integration
+name: CI
+on: [push]
+
+jobs:
+  build:
+    runs-on: ubuntu-latest   # the cheapest runner
+    steps:
+      # fetch the sources
+      - uses: "actions/checkout@v3" # pinned by tag
+      - name: Build
+        run: |
+          cargo build
+          cargo test
+
+      - name: Lint
+        run: 'cargo clippy'
+"#;
+
+fn document() -> Document {
+    Document::parse(WORKFLOW).expect("Failed to parse YAML")
+}
+
+#[test]
+fn test_set_run_keeps_the_rest_of_the_file() {
+    let mut document = document();
+    document
+        .set_run("build", 1, "cargo build --release")
+        .unwrap();
+    assert_eq!(
+        document.as_str(),
+        WORKFLOW.replace(
+            "|\n          cargo build\n          cargo test",
+            "cargo build --release"
+        )
+    );
+
+    document
+        .set_run("build", 2, "cargo fmt --check\ncargo clippy")
+        .unwrap();
+    assert_eq!(
+        document.as_str(),
+        WORKFLOW
+            .replace(
+                "|\n          cargo build\n          cargo test",
+                "cargo build --release"
+            )
+            .replace(
+                "'cargo clippy'",
+                "|-\n          cargo fmt --check\n          cargo clippy"
+            )
+    );
+    assert_eq!(
+        document.workflow().get_job("build").unwrap().steps[2]
+            .run
+            .as_deref(),
+        Some("cargo fmt --check\ncargo clippy")
+    );
+}
+
+#[test]
+fn test_set_uses_ref_keeps_quotes_and_comments() {
+    let mut document = document();
+    document.set_uses_ref("build", 0, "v4").unwrap();
+    assert_eq!(
+        document.as_str(),
+        WORKFLOW.replace("checkout@v3\"", "checkout@v4\"")
+    );
+    assert!(matches!(
+        document.set_uses_ref("build", 1, "v4"),
+        Err(Error::InvalidEdit { .. })
+    ));
+
+    // the ref starts at the first `@`, as `Uses` reads it
+    let yaml = WORKFLOW.replace("checkout@v3", "checkout@feature@x");
+    let mut document = Document::parse(&yaml).unwrap();
+    document.set_uses_ref("build", 0, "v4").unwrap();
+    assert_eq!(
+        document.as_str(),
+        WORKFLOW.replace("checkout@v3", "checkout@v4")
+    );
+}
+
+#[test]
+fn test_set_env_adds_and_replaces_variables() {
+    let mut document = document();
+    document
+        .set_env(EnvTarget::Workflow, "CARGO_TERM_COLOR", "always")
+        .unwrap();
+    document
+        .set_env(EnvTarget::Job("build"), "RUST_LOG", "debug")
+        .unwrap();
+    document
+        .set_env(EnvTarget::Step("build", 2), "VERBOSE", "true")
+        .unwrap();
+    document
+        .set_env(EnvTarget::Job("build"), "RUST_LOG", "info")
+        .unwrap();
+    assert_eq!(
+        document.as_str(),
+        WORKFLOW
+            .replace("\njobs:\n", "\nenv:\n  CARGO_TERM_COLOR: always\njobs:\n")
+            .replace(
+                "    steps:\n",
+                "    env:\n      RUST_LOG: info\n    steps:\n"
+            )
+            .replace(
+                "'cargo clippy'\n",
+                "'cargo clippy'\n        env:\n          VERBOSE: 'true'\n"
+            )
+    );
+    let step = &document.workflow().get_job("build").unwrap().steps[2];
+    assert_eq!(step.env.as_ref().unwrap()["VERBOSE"], "true");
+}
+
+#[test]
+fn test_set_env_in_existing_mappings() {
+    let yaml_data = r#"on: push
+env: { A: "1" }
+jobs:
+  test:
+    runs-on: ubuntu-latest
+    env:
+      B: 2 # two
+    steps:
+      - run: cargo test
+"#;
+    let mut document = Document::parse(yaml_data).unwrap();
+    document.set_env(EnvTarget::Workflow, "C", "3").unwrap();
+    document.set_env(EnvTarget::Job("test"), "D", "4").unwrap();
+    document
+        .set_env(EnvTarget::Job("test"), "B", "two")
+        .unwrap();
+    assert_eq!(
+        document.as_str(),
+        yaml_data
+            .replace(r#"{ A: "1" }"#, r#"{ A: "1", C: '3' }"#)
+            .replace("B: 2 # two\n", "B: two # two\n      D: '4'\n")
+    );
+}
+
+#[test]
+fn test_insert_step() {
+    let mut document = document();
+    let audit = Step::builder().name("Audit").run("cargo audit").build();
+    document.insert_step("build", 0, &audit).unwrap();
+    document.insert_step("build", 4, &audit).unwrap();
+    assert_eq!(
+        document.as_str(),
+        WORKFLOW
+            .replace(
+                "      # fetch",
+                "      - name: Audit\n        run: cargo audit\n      # fetch"
+            )
+            .replace(
+                "'cargo clippy'\n",
+                "'cargo clippy'\n      - name: Audit\n        run: cargo audit\n"
+            )
+    );
+    assert_eq!(document.workflow().get_job("build").unwrap().steps.len(), 5);
+    assert!(matches!(
+        document.insert_step("build", 9, &audit),
+        Err(Error::InvalidEdit { .. })
+    ));
+    assert!(matches!(
+        document.insert_step("deploy", 0, &audit),
+        Err(Error::JobNotFound { .. })
+    ));
+}
@@ -0,0 +1,130 @@
+mod common;
+
+use actionoscope::expression::{self, Value};
+use actionoscope::{Error, Step, StepContext};
+use common::{contexts, eval};
+
+#[test]
+fn test_literals() {
+    assert_eq!(eval("null"), Value::Null);
+    assert_eq!(eval("true"), Value::Boolean(true));
+    assert_eq!(eval("-9.2"), Value::Number(-9.2));
+    assert_eq!(eval("0xff"), Value::Number(255.0));
+    assert_eq!(eval("2.99e-2"), Value::Number(0.0299));
+    assert_eq!(
+        eval("'It''s open source!'"),
+        Value::from("It's open source!")
+    );
+    assert_eq!(eval("(((1)))"), Value::Number(1.0));
+}
+
+#[test]
+fn test_loose_equality_and_comparison() {
+    for source in [
+        "'abc' == 'ABC'",
+        "1 == '1'",
+        "'' == 0",
+        "null == false",
+        "true == '1'",
+        "' 0x10 ' == 16",
+        "'a' < 'B'",
+        "2 > '10' == false",
+        "github.event == github.event == false",
+    ] {
+        assert_eq!(eval(source), Value::Boolean(true), "{source}");
+    }
+    assert_eq!(eval("'abc' == 1"), Value::Boolean(false));
+    assert_eq!(eval("NaN == NaN"), Value::Boolean(false));
+    assert_eq!(eval("'x' >= 0 || 'x' <= 0"), Value::Boolean(false));
+}
+
+#[test]
+fn test_logical_operators_return_operands() {
+    assert_eq!(eval("env.EMPTY || 'default'"), Value::from("default"));
+    assert_eq!(eval("env.region || 'default'"), Value::from("eu-west-1"));
+    assert_eq!(eval("env.missing && 'never'"), Value::Null);
+    assert_eq!(eval("'a' && 0"), Value::Number(0.0));
+    assert_eq!(eval("!env.missing && !!'x'"), Value::Boolean(true));
+    assert_eq!(eval("!0 == true && 1 < 2"), Value::Boolean(true));
+}
+
+#[test]
+fn test_property_access_indexing_and_filters() {
+    assert_eq!(
+        eval("GITHUB.SHA"),
+        Value::from("ffac537e6cbbf934b08745a378932722df287a53")
+    );
+    assert_eq!(eval("github['sha'] == github.sha"), Value::Boolean(true));
+    assert_eq!(
+        eval("github.event.commits[1].message"),
+        Value::from("second")
+    );
+    assert_eq!(eval("github.event.commits[2]"), Value::Null);
+    assert_eq!(eval("github.sha.length"), Value::Null);
+    assert_eq!(eval("matrix.os"), Value::Null);
+    assert_eq!(
+        eval("github.event.commits.*.message"),
+        Value::from(vec!["first", "second"])
+    );
+    assert_eq!(
+        eval("needs.*.result"),
+        Value::from(vec!["success", "failure"])
+    );
+}
+
+#[test]
+fn test_errors_point_at_the_problem() {
+    let error = |source: &str| expression::evaluate(source, &contexts()).unwrap_err();
+
+    let err = error("env.A == == 'b'");
+    assert_eq!(err.message, "Unexpected symbol: '=='");
+    assert_eq!(err.position, 10);
+    assert_eq!(
+        err.to_string(),
+        "Unexpected symbol: '=='. Located at position 10 within expression: env.A == == 'b'"
+    );
+    assert_eq!(error("env.A &&").message, "Unexpected end of expression");
+    assert_eq!(error("(1 == 1").message, "Unexpected end of expression");
+    assert_eq!(error("'open").message, "Unterminated string: 'open");
+    assert_eq!(error("1 = 1").position, 3);
+    assert_eq!(
+        error("github.sha == foo.bar").message,
+        "Unrecognized named-value: 'foo'"
+    );
+    assert_eq!(error("env.").message, "Unexpected end of expression");
+}
+
+#[test]
+fn test_interpolation() {
+    let contexts = contexts();
+    assert_eq!(
+        expression::interpolate(
+            "deploy ${{ github.sha }} to ${{ env.REGION || 'us-east-1' }} (${{ '}}' }})",
+            &contexts
+        )
+        .unwrap(),
+        "deploy ffac537e6cbbf934b08745a378932722df287a53 to eu-west-1 (}})"
+    );
+    assert_eq!(
+        expression::interpolate("${{ 1 == 1 }} ${{ 3.50 }} ${{ needs }}", &contexts).unwrap(),
+        "true 3.5 Object"
+    );
+    assert!(expression::interpolate("${{ env.REGION", &contexts).is_err());
+}
+
+#[test]
+fn test_step_with_invalid_expression_is_not_run() {
+    let step: Step = serde_yaml::from_str(
+        r#"
+        name: Broken
+        run: echo ${{ env.A = 'b' }}
+        "#,
+    )
+    .unwrap();
+    let err = step.run_in(&StepContext::default()).unwrap_err();
+    assert!(matches!(err, Error::Expression { .. }));
+    assert_eq!(
+        err.to_string(),
+        "Step 'Broken' has an invalid expression: Unexpected symbol: '='. Located at position 7 within expression: env.A = 'b'"
+    );
+}
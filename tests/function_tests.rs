@@ -0,0 +1,112 @@
+mod common;
+
+use actionoscope::expression::{self, Value};
+use common::{contexts, eval};
+
+#[test]
+fn test_contains_starts_with_and_ends_with_ignore_case() {
+    for source in [
+        "contains('Hello world', 'WORLD')",
+        "contains(github.event.issue.labels.*.name, 'help wanted')",
+        "contains(fromJSON('[\"push\", \"pull_request\"]'), github.event_name)",
+        "contains(fromJSON('[1, 2]'), '2')",
+        "startsWith(github.ref, 'refs/heads/release-')",
+        "endsWith(github.ref, '-1.2')",
+        "startsWith('', '')",
+        "!contains(github.event.issue.labels.*.name, 'help')",
+        "!endsWith(github.ref, 'refs')",
+    ] {
+        assert_eq!(eval(source), Value::Boolean(true), "{source}");
+    }
+}
+
+#[test]
+fn test_format() {
+    assert_eq!(
+        eval("format('Hello {0} {1} {2}', 'Mona', 'the', 'Octocat')"),
+        Value::from("Hello Mona the Octocat")
+    );
+    assert_eq!(
+        eval("format('{{Hello {0} {1} {2}!}}', 'Mona', 'the', 'Octocat')"),
+        Value::from("{Hello Mona the Octocat!}")
+    );
+    assert_eq!(
+        eval("format('{0}{0} {1} {2}', 1.5, true, null)"),
+        Value::from("1.51.5 true ")
+    );
+
+    let error = |source: &str| {
+        expression::evaluate(source, &contexts())
+            .unwrap_err()
+            .message
+    };
+    assert_eq!(
+        error("format('{0} {1}', 'a')"),
+        "The following format string references more arguments than were supplied: {0} {1}"
+    );
+    assert_eq!(
+        error("format('{0', 'a')"),
+        "The following format string is invalid: {0"
+    );
+    assert_eq!(
+        error("format('a } b')"),
+        "The following format string is invalid: a } b"
+    );
+}
+
+#[test]
+fn test_join() {
+    assert_eq!(
+        eval("join(github.event.issue.labels.*.name, ', ')"),
+        Value::from("bug, Help Wanted")
+    );
+    assert_eq!(
+        eval("join(fromJSON('[1, true, null]'))"),
+        Value::from("1,true,")
+    );
+    assert_eq!(eval("join('abc', '-')"), Value::from("abc"));
+}
+
+#[test]
+fn test_to_json_and_from_json() {
+    assert_eq!(
+        eval("fromJSON(env.MATRIX).os[1]"),
+        Value::from("windows-latest")
+    );
+    assert_eq!(eval("fromJSON('true') == true"), Value::Boolean(true));
+    assert_eq!(eval("fromJSON(' 42 ')"), Value::Number(42.0));
+    assert_eq!(
+        eval("toJSON(fromJSON(env.MATRIX))"),
+        Value::from(
+            "{\n  \"os\": [\n    \"ubuntu-latest\",\n    \"windows-latest\"\n  ],\n  \"debug\": true\n}"
+        )
+    );
+    assert_eq!(eval("toJSON(1.5)"), Value::from("1.5"));
+    assert_eq!(eval("toJSON('a')"), Value::from("\"a\""));
+
+    let err = expression::evaluate("fromJSON('{')", &contexts()).unwrap_err();
+    assert!(err.message.starts_with("Invalid JSON given to fromJSON"));
+    assert_eq!(err.position, 1);
+}
+
+#[test]
+fn test_calls_are_checked_when_parsed() {
+    let error = |source: &str| expression::Expression::parse(source).unwrap_err();
+
+    let err = error("github.ref && contain('a', 'b')");
+    assert_eq!(err.message, "Unrecognized function: 'contain'");
+    assert_eq!(err.position, 15);
+    assert_eq!(
+        error("startsWith('a')").message,
+        "Too few parameters supplied: 'startsWith'"
+    );
+    assert_eq!(
+        error("toJSON(1, 2)").message,
+        "Too many parameters supplied: 'toJSON'"
+    );
+    assert_eq!(error("join('a',)").message, "Unexpected symbol: ')'");
+    assert_eq!(
+        eval("STARTSWITH(Format('{0}', 'abc'), 'A')"),
+        Value::Boolean(true)
+    );
+}
@@ -0,0 +1,137 @@
+mod common;
+
+use actionoscope::expression::{self, hash_files, Contexts, Value};
+use actionoscope::{Error, Step, StepContext};
+use std::fs;
+use tempfile::TempDir;
+
+// The expected hashes were computed independently: SHA-256 over the
+// concatenated SHA-256 digests of the files, in the runner's walk order.
+const ALL_LOCKS: &str = "05d21398490cca076e36115dc20bbbe1884c441810cf8c149c80bf730077e5db";
+const LOCKS_OUTSIDE_TARGET: &str =
+    "8fa0cb33d137c7ed1d43a1753168e6a7dd30f3a61847566eb632977b7e5c6a5a";
+const ROOT_LOCK: &str = "225f1bbbc4b1a3d97c622a492d91ea8d6858d7e1164bbbb5953f74311d6d222e";
+const CRATE_LOCK_THEN_ROOT_LOCK: &str =
+    "5e48c52efd764422ec109a0a745c8651ff9fabb8aa4870e46ad44929a811a325";
+
+/// A scratch workspace with lock files at the root, in a crate, in a build
+/// directory and in a hidden directory.
+fn scratch_workspace() -> TempDir {
+    let scratch = common::scratch_dir();
+    let root = scratch.path();
+    for (path, contents) in [
+        ("Cargo.lock", "a\n"),
+        ("crates/x/Cargo.lock", "b\n"),
+        ("target/debug/Cargo.lock", "c\n"),
+        (".hidden/Cargo.lock", "d\n"),
+        ("README.md", "# Readme\n"),
+    ] {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+    scratch
+}
+
+#[test]
+fn test_hash_files_walks_the_workspace_like_the_runner() {
+    let scratch = scratch_workspace();
+    let root = scratch.path();
+    assert_eq!(hash_files(root, &["**/Cargo.lock"]).unwrap(), ALL_LOCKS);
+    assert_eq!(hash_files(root, &["./**/*.lock"]).unwrap(), ALL_LOCKS);
+    assert_eq!(hash_files(root, &["Cargo.lock"]).unwrap(), ROOT_LOCK);
+    assert_eq!(
+        hash_files(root, &[root.join("Cargo.lock").to_str().unwrap()]).unwrap(),
+        ROOT_LOCK
+    );
+    assert_eq!(hash_files(root, &["*.lock"]).unwrap(), ROOT_LOCK);
+    assert_eq!(hash_files(root, &["**/*.toml"]).unwrap(), "");
+}
+
+#[test]
+fn test_hash_files_exclusions() {
+    let scratch = scratch_workspace();
+    let root = scratch.path();
+    assert_eq!(
+        hash_files(root, &["**/Cargo.lock", "!target/**"]).unwrap(),
+        LOCKS_OUTSIDE_TARGET
+    );
+    // a directory matches the files under it
+    assert_eq!(
+        hash_files(root, &["**/Cargo.lock\n!target\n# comment\n"]).unwrap(),
+        LOCKS_OUTSIDE_TARGET
+    );
+    // the last pattern matching a file decides
+    assert_eq!(
+        hash_files(root, &["**/Cargo.lock", "!**/Cargo.lock", "Cargo.lock"]).unwrap(),
+        ROOT_LOCK
+    );
+    assert_eq!(hash_files(root, &["!Cargo.lock"]).unwrap(), "");
+}
+
+#[test]
+fn test_hash_files_orders_files_by_search_path() {
+    let scratch = scratch_workspace();
+    let root = scratch.path();
+    assert_eq!(
+        hash_files(root, &["crates/**", "Cargo.lock"]).unwrap(),
+        CRATE_LOCK_THEN_ROOT_LOCK
+    );
+    // a search path inside another one is walked as part of it
+    assert_eq!(
+        hash_files(root, &["**/Cargo.lock", "target/**/Cargo.lock"]).unwrap(),
+        ALL_LOCKS
+    );
+}
+
+#[test]
+fn test_hash_files_in_expressions() {
+    let scratch = scratch_workspace();
+    let root = scratch.path();
+    let mut contexts = Contexts::new();
+    contexts.insert(
+        "github",
+        Value::object([("workspace", root.display().to_string())]),
+    );
+    assert_eq!(
+        expression::evaluate(
+            "format('cargo-{0}', hashFiles('**/Cargo.lock', '!target'))",
+            &contexts
+        )
+        .unwrap(),
+        Value::from(format!("cargo-{LOCKS_OUTSIDE_TARGET}"))
+    );
+    assert_eq!(
+        expression::evaluate("hashFiles('missing') == ''", &contexts).unwrap(),
+        Value::Boolean(true)
+    );
+    assert_eq!(
+        expression::Expression::parse("hashFiles()")
+            .unwrap_err()
+            .message,
+        "Too few parameters supplied: 'hashFiles'"
+    );
+}
+
+#[test]
+fn test_step_hashes_files_of_its_workspace() {
+    let scratch = scratch_workspace();
+    let root = scratch.path();
+    let context = StepContext {
+        workspace: Some(root.to_path_buf()),
+        ..Default::default()
+    };
+    let step = Step::builder()
+        .name("Cache key")
+        .run(format!(
+            "test \"${{{{ hashFiles('Cargo.lock') }}}}\" = {ROOT_LOCK}"
+        ))
+        .build();
+    assert!(step.run_in(&context).is_ok());
+
+    let step = Step::builder()
+        .name("Wrong key")
+        .run("test \"${{ hashFiles('**/Cargo.lock') }}\" = nope")
+        .build();
+    assert!(matches!(step.run_in(&context), Err(Error::Exit { .. })));
+}
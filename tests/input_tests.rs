@@ -0,0 +1,164 @@
+use actionoscope::{Error, InputType, InputValue, Workflow};
+use std::collections::HashMap;
+
+const WORKFLOW: &str = r#"
+on:
+  workflow_dispatch:
+    inputs:
+      environment:
+        description: Where to deploy
+        required: true
+        type: choice
+        options: [staging, production]
+      dry-run:
+        type: boolean
+      replicas:
+        type: number
+        default: 2
+      tag:
+        description: Image tag
+  workflow_call:
+    inputs:
+      tag:
+        type: string
+        required: true
+      region:
+        type: string
+        default: eu-west-1
+jobs:
+  deploy:
+    runs-on: ubuntu-latest
+    steps:
+      - run: test "${{ inputs.environment }} ${{ github.event.inputs.replicas }}" = "production 3"
+"#;
+
+fn provided(values: &[(&str, &str)]) -> HashMap<String, String> {
+    values
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect()
+}
+
+#[test]
+fn test_inputs_are_typed() {
+    let workflow = Workflow::from_yaml(WORKFLOW).expect("Failed to parse YAML");
+    let inputs = workflow.on.inputs();
+    let names: Vec<&str> = inputs.keys().copied().collect();
+    assert_eq!(
+        names,
+        vec!["environment", "dry-run", "replicas", "tag", "region"]
+    );
+    let environment = inputs["environment"];
+    assert!(environment.required);
+    assert_eq!(environment.input_type, InputType::Choice);
+    assert_eq!(environment.options, vec!["staging", "production"]);
+    assert_eq!(inputs["replicas"].default, Some(InputValue::Number(2.0)));
+    // declared by both triggers: workflow_dispatch describes it
+    assert_eq!(inputs["tag"].description.as_deref(), Some("Image tag"));
+}
+
+#[test]
+fn test_resolve_inputs_applies_defaults() {
+    let workflow = Workflow::from_yaml(WORKFLOW).expect("Failed to parse YAML");
+    let inputs = workflow
+        .resolve_inputs(&provided(&[("environment", "staging")]))
+        .unwrap();
+    assert_eq!(
+        inputs["environment"],
+        InputValue::String("staging".to_string())
+    );
+    assert_eq!(inputs["dry-run"], InputValue::Boolean(false));
+    assert_eq!(inputs["replicas"], InputValue::Number(2.0));
+    assert_eq!(inputs["region"].to_string(), "eu-west-1");
+    assert!(!inputs.contains_key("tag"));
+}
+
+#[test]
+fn test_resolve_inputs_validates_values() {
+    let workflow = Workflow::from_yaml(WORKFLOW).expect("Failed to parse YAML");
+    let cases = [
+        (vec![], "environment", "it is required"),
+        (
+            vec![("environment", "qa")],
+            "environment",
+            "expected one of staging, production",
+        ),
+        (
+            vec![("environment", "staging"), ("dry-run", "yes")],
+            "dry-run",
+            "expected `true` or `false`",
+        ),
+        (
+            vec![("environment", "staging"), ("replicas", "many")],
+            "replicas",
+            "expected a number",
+        ),
+        (
+            vec![("environment", "staging"), ("colour", "blue")],
+            "colour",
+            "does not declare it",
+        ),
+    ];
+    for (values, input, reason) in cases {
+        match workflow.resolve_inputs(&provided(&values)) {
+            Err(Error::InvalidInput { name, reason: got }) => {
+                assert_eq!(name, input);
+                assert!(got.contains(reason), "{got}");
+            }
+            other => panic!("expected an invalid input error, got {other:?}"),
+        }
+    }
+}
+
+#[test]
+fn test_defaults_are_checked_against_the_type() {
+    let workflow = Workflow::from_yaml(
+        r#"
+on:
+  workflow_dispatch:
+    inputs:
+      dry-run:
+        type: boolean
+        default: 'false'
+      level:
+        type: choice
+        options: [low, high]
+        default: medium
+jobs:
+  deploy:
+    runs-on: ubuntu-latest
+    steps:
+      - run: echo deploying
+"#,
+    )
+    .expect("Failed to parse YAML");
+    let inputs = workflow
+        .resolve_inputs(&provided(&[("level", "low")]))
+        .unwrap();
+    // a quoted default is still a boolean, and a falsy one
+    assert_eq!(inputs["dry-run"], InputValue::Boolean(false));
+    match workflow.resolve_inputs(&provided(&[])) {
+        Err(Error::InvalidInput { name, reason }) => {
+            assert_eq!(name, "level");
+            assert_eq!(
+                reason,
+                "its default is invalid: expected one of low, high, got `medium`"
+            );
+        }
+        other => panic!("expected an invalid input error, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_inputs_are_substituted_in_steps() {
+    let workflow = Workflow::from_yaml(WORKFLOW).expect("Failed to parse YAML");
+    let inputs = workflow
+        .resolve_inputs(&provided(&[
+            ("environment", "production"),
+            ("replicas", "3"),
+        ]))
+        .unwrap();
+    let job = workflow.get_job("deploy").unwrap();
+    let context = workflow.step_context_with_inputs(job, None, inputs);
+    assert!(job.steps[0].run_in(&context).is_ok());
+}
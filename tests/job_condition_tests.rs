@@ -0,0 +1,205 @@
+mod common;
+
+use actionoscope::{Error, JobResult, StepContext, Workflow};
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::collections::HashMap;
+use std::fs;
+
+const WORKFLOW: &str = r#"
+on: push
+jobs:
+  build:
+    runs-on: ubuntu-latest
+    steps:
+      - run: cargo build
+  test:
+    needs: build
+    runs-on: ubuntu-latest
+    steps:
+      - run: cargo test
+  deploy:
+    needs: test
+    if: ${{ needs.test.result == 'success' }}
+    runs-on: ubuntu-latest
+    steps:
+      - run: ./deploy.sh
+  report:
+    needs: [build, test]
+    if: always()
+    runs-on: ubuntu-latest
+    steps:
+      - run: ./report.sh
+  notify:
+    needs: deploy
+    if: failure()
+    runs-on: ubuntu-latest
+    steps:
+      - run: ./notify.sh
+"#;
+
+fn met(workflow: &Workflow, job: &str, results: &[(&str, JobResult)]) -> bool {
+    let results: HashMap<String, JobResult> = results
+        .iter()
+        .map(|(name, result)| (name.to_string(), *result))
+        .collect();
+    workflow
+        .job_condition_met(job, &results, &StepContext::default())
+        .unwrap()
+}
+
+#[test]
+fn test_jobs_without_if_need_every_ancestor_to_succeed() {
+    use JobResult::*;
+    let workflow = Workflow::from_yaml(WORKFLOW).unwrap();
+    assert!(met(&workflow, "build", &[]));
+    assert!(met(&workflow, "test", &[("build", Success)]));
+    assert!(!met(&workflow, "test", &[("build", Failure)]));
+    assert!(!met(&workflow, "test", &[("build", Skipped)]));
+    // a job that has not run counts as skipped
+    assert!(!met(&workflow, "test", &[]));
+    assert_eq!(
+        workflow.job_ancestors("notify"),
+        ["deploy", "test", "build"]
+    );
+}
+
+#[test]
+fn test_job_if_sees_needs_results() {
+    use JobResult::*;
+    let workflow = Workflow::from_yaml(WORKFLOW).unwrap();
+    assert_eq!(
+        workflow.get_job("report").unwrap().condition.as_deref(),
+        Some("always()")
+    );
+    assert!(met(
+        &workflow,
+        "deploy",
+        &[("build", Success), ("test", Success)]
+    ));
+    assert!(!met(
+        &workflow,
+        "deploy",
+        &[("build", Success), ("test", Failure)]
+    ));
+    assert!(met(
+        &workflow,
+        "report",
+        &[("build", Failure), ("test", Skipped)]
+    ));
+    assert!(workflow.to_yaml().contains("if: always()"));
+}
+
+#[test]
+fn test_status_functions_look_at_all_ancestors() {
+    use JobResult::*;
+    let workflow = Workflow::from_yaml(WORKFLOW).unwrap();
+    // the failure is two jobs up
+    assert!(met(
+        &workflow,
+        "notify",
+        &[("build", Failure), ("test", Skipped), ("deploy", Skipped)]
+    ));
+    assert!(!met(
+        &workflow,
+        "notify",
+        &[("build", Success), ("test", Success), ("deploy", Skipped)]
+    ));
+    assert!(!met(
+        &workflow,
+        "notify",
+        &[("build", Success), ("test", Success), ("deploy", Success)]
+    ));
+}
+
+#[test]
+fn test_invalid_job_if_is_reported() {
+    let workflow = Workflow::from_yaml(
+        r#"
+on: push
+jobs:
+  build:
+    if: needs.test.result ==
+    runs-on: ubuntu-latest
+    steps:
+      - run: cargo build
+"#,
+    )
+    .unwrap();
+    let err = workflow
+        .job_condition_met("build", &HashMap::new(), &StepContext::default())
+        .unwrap_err();
+    assert!(matches!(err, Error::JobExpression { .. }));
+    assert!(err
+        .to_string()
+        .contains("Job 'build' has an invalid expression: Unexpected end of expression"));
+}
+
+#[test]
+fn test_run_skips_jobs_after_a_failure() {
+    let scratch = common::scratch_repository();
+    let root = scratch.path();
+    let workflow = root.join("ci.yml");
+    fs::write(
+        &workflow,
+        r#"on: push
+jobs:
+  build:
+    runs-on: ubuntu-latest
+    steps:
+      - run: echo building && exit 2
+  lint:
+    runs-on: ubuntu-latest
+    steps:
+      - run: echo linting
+  test:
+    needs: build
+    runs-on: ubuntu-latest
+    steps:
+      - run: echo testing
+  deploy:
+    needs: test
+    runs-on: ubuntu-latest
+    steps:
+      - run: echo deploying
+  report:
+    needs: deploy
+    if: always() && needs.deploy.result == 'skipped'
+    runs-on: ubuntu-latest
+    steps:
+      - run: echo "reporting ${{ needs.deploy.result }}"
+        if: needs.deploy.result == 'skipped'
+  release:
+    needs: lint
+    if: github.ref == 'refs/heads/main'
+    runs-on: ubuntu-latest
+    steps:
+      - run: echo releasing
+"#,
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("actionoscope").unwrap();
+    cmd.arg("run")
+        .arg("--repo")
+        .arg(root)
+        .arg("-w")
+        .arg(&workflow);
+    cmd.assert()
+        .code(1)
+        .stdout(predicate::str::contains("[cmd]: building"))
+        .stdout(predicate::str::contains("[cmd]: linting"))
+        .stdout(predicate::str::contains(
+            "Skipping job 'test' because job 'build' failed",
+        ))
+        .stdout(predicate::str::contains(
+            "Skipping job 'deploy' because job 'test' was skipped",
+        ))
+        .stdout(predicate::str::contains("[cmd]: reporting skipped"))
+        .stdout(predicate::str::contains(
+            "Skipping job 'release': its condition `github.ref == 'refs/heads/main'` is false",
+        ))
+        .stdout(predicate::str::contains("[cmd]: testing").not())
+        .stdout(predicate::str::contains("[cmd]: deploying").not())
+        .stdout(predicate::str::contains("[cmd]: releasing").not());
+}
@@ -0,0 +1,111 @@
+use actionoscope::{Access, Permissions, PermissionsSource, Scope, Workflow};
+
+const WORKFLOW: &str = r#"
+on: push
+permissions:
+  contents: read
+  pull-requests: write
+jobs:
+  inherit:
+    runs-on: ubuntu-latest
+    steps:
+      - run: echo "inherit"
+  release:
+    runs-on: ubuntu-latest
+    permissions:
+      contents: write
+      id-token: write
+    steps:
+      - run: echo "release"
+  locked:
+    runs-on: ubuntu-latest
+    permissions: {}
+    steps:
+      - run: echo "locked"
+  audit:
+    runs-on: ubuntu-latest
+    permissions: read-all
+    steps:
+      - run: echo "audit"
+"#;
+
+#[test]
+fn test_permissions_forms() {
+    let workflow = Workflow::from_yaml(WORKFLOW).expect("Failed to parse YAML");
+    let Some(Permissions::Scopes(scopes)) = &workflow.permissions else {
+        panic!("workflow permissions not parsed");
+    };
+    assert_eq!(scopes[&Scope::PullRequests], Access::Write);
+    assert_eq!(
+        workflow.get_job("locked").unwrap().permissions,
+        Some(Permissions::Scopes(Default::default()))
+    );
+    assert_eq!(
+        workflow.get_job("audit").unwrap().permissions,
+        Some(Permissions::ReadAll)
+    );
+}
+
+#[test]
+fn test_job_permissions_replace_workflow_permissions() {
+    let workflow = Workflow::from_yaml(WORKFLOW).expect("Failed to parse YAML");
+
+    let inherit = workflow.effective_permissions(workflow.get_job("inherit").unwrap());
+    assert_eq!(inherit.source, PermissionsSource::Workflow);
+    let scopes = inherit.scopes().unwrap();
+    assert_eq!(scopes[&Scope::Contents], Access::Read);
+    assert_eq!(scopes[&Scope::PullRequests], Access::Write);
+    assert_eq!(scopes[&Scope::Issues], Access::None);
+
+    let release = workflow.effective_permissions(workflow.get_job("release").unwrap());
+    assert_eq!(release.source, PermissionsSource::Job);
+    let scopes = release.scopes().unwrap();
+    assert_eq!(scopes[&Scope::Contents], Access::Write);
+    assert_eq!(scopes[&Scope::IdToken], Access::Write);
+    assert_eq!(scopes[&Scope::PullRequests], Access::None);
+
+    let audit = workflow.effective_permissions(workflow.get_job("audit").unwrap());
+    assert!(audit
+        .scopes()
+        .unwrap()
+        .values()
+        .all(|access| *access == Access::Read));
+}
+
+#[test]
+fn test_repository_default_permissions() {
+    let yaml_data = r#"
+on: push
+jobs:
+  build:
+    runs-on: ubuntu-latest
+    steps:
+      - run: cargo build
+"#;
+    let workflow = Workflow::from_yaml(yaml_data).expect("Failed to parse YAML");
+    let effective = workflow.effective_permissions(workflow.get_job("build").unwrap());
+    assert_eq!(effective.source, PermissionsSource::RepositoryDefault);
+    assert!(effective.scopes().is_none());
+}
+
+#[test]
+fn test_invalid_permissions() {
+    let yaml_data = r#"
+on: push
+permissions: read
+jobs:
+  build:
+    runs-on: ubuntu-latest
+    permissions:
+      contents: admin
+    steps:
+      - run: cargo build
+"#;
+    let err = Workflow::from_yaml(yaml_data).unwrap_err();
+    assert!(err.message.contains("invalid permissions `read`"));
+
+    let yaml_data = yaml_data.replace("permissions: read\n", "");
+    let err = Workflow::from_yaml(&yaml_data).unwrap_err();
+    assert!(err.message.starts_with("jobs.build.permissions"));
+    assert!(err.message.contains("unknown variant `admin`"));
+}
@@ -0,0 +1,263 @@
+mod common;
+
+use actionoscope::repository;
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+use tempfile::TempDir;
+
+/// A scratch repository with a workflow, a README and an editor swap file in
+/// its workflows directory.
+fn scratch_repository() -> TempDir {
+    let scratch = common::scratch_repository();
+    let root = scratch.path();
+    let workflows = root.join(".github").join("workflows");
+    fs::create_dir_all(&workflows).unwrap();
+    fs::create_dir_all(root.join("src").join("nested")).unwrap();
+    fs::write(
+        workflows.join("ci.yml"),
+        "on: push\njobs:\n  test:\n    runs-on: ubuntu-latest\n    steps:\n      - run: pwd\n",
+    )
+    .unwrap();
+    fs::write(
+        workflows.join("audit.yaml"),
+        "on: push\njobs:\n  audit:\n    runs-on: ubuntu-latest\n    steps:\n      - run: echo audit\n",
+    )
+    .unwrap();
+    fs::write(workflows.join("README.md"), "# Workflows\n").unwrap();
+    fs::write(workflows.join(".ci.yml.swp"), [0u8, 1, 2]).unwrap();
+    scratch
+}
+
+#[test]
+fn test_find_root_walks_up_to_the_repository() {
+    let scratch = scratch_repository();
+    let root = scratch.path();
+    assert_eq!(
+        repository::find_root(root.join("src").join("nested")),
+        Some(root.to_path_buf())
+    );
+
+    // worktrees and submodules have a `.git` file
+    let submodule = root.join("src").join("vendored");
+    fs::create_dir_all(&submodule).unwrap();
+    fs::write(
+        submodule.join(".git"),
+        "gitdir: ../../.git/modules/vendored\n",
+    )
+    .unwrap();
+    assert_eq!(repository::find_root(&submodule), Some(submodule));
+}
+
+#[test]
+fn test_workflow_files_skips_other_files() {
+    let scratch = scratch_repository();
+    let root = scratch.path();
+    assert_eq!(
+        repository::workflow_files(root).unwrap(),
+        [
+            root.join(".github/workflows/audit.yaml"),
+            root.join(".github/workflows/ci.yml"),
+        ]
+    );
+    assert!(repository::is_workflow_file("ci.yaml"));
+    assert!(!repository::is_workflow_file("ci.yml.swp"));
+}
+
+#[test]
+fn test_ls_from_a_subdirectory() {
+    let scratch = scratch_repository();
+    let root = scratch.path();
+    let mut cmd = Command::cargo_bin("actionoscope").unwrap();
+    cmd.current_dir(root.join("src").join("nested")).arg("ls");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("audit.yaml"))
+        .stdout(predicate::str::contains("ci.yml"))
+        .stdout(predicate::str::contains("README").not());
+}
+
+#[test]
+fn test_run_against_another_checkout() {
+    let scratch = scratch_repository();
+    let root = scratch.path();
+    let mut cmd = Command::cargo_bin("actionoscope").unwrap();
+    cmd.arg("run")
+        .arg("--repo")
+        .arg(root.join("src"))
+        .arg("-w")
+        .arg("ci.yml");
+    // steps run at the root of the repository, as on a runner
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "[cmd]: {}",
+            fs::canonicalize(root).unwrap().display()
+        )));
+}
+
+#[test]
+fn test_select_workflows_by_glob_name_and_path() {
+    let scratch = scratch_repository();
+    let root = scratch.path();
+    let workflows = root.join(".github").join("workflows");
+    fs::write(
+        workflows.join("ci-linux.yml"),
+        "name: Continuous Integration\non: push\njobs:\n  test:\n    runs-on: ubuntu-latest\n    steps:\n      - run: echo linux\n",
+    )
+    .unwrap();
+    let selectors = |selectors: &[&str]| -> Vec<String> {
+        selectors
+            .iter()
+            .map(|selector| selector.to_string())
+            .collect()
+    };
+
+    assert_eq!(
+        repository::select_workflows(root, &selectors(&["ci*.yml"])).unwrap(),
+        [workflows.join("ci-linux.yml"), workflows.join("ci.yml")]
+    );
+    assert_eq!(
+        repository::select_workflows(
+            root,
+            &selectors(&["Continuous Integration", "audit.yaml", "ci-*.yml"])
+        )
+        .unwrap(),
+        [workflows.join("ci-linux.yml"), workflows.join("audit.yaml")]
+    );
+    assert!(matches!(
+        repository::select_workflows(root, &selectors(&["deploy-*.yml"])),
+        Err(actionoscope::Error::WorkflowNotFound { .. })
+    ));
+}
+
+#[test]
+fn test_ambiguous_workflow_name() {
+    let scratch = scratch_repository();
+    let root = scratch.path();
+    let workflows = root.join(".github").join("workflows");
+    for file in ["ci-linux.yml", "ci-macos.yml"] {
+        fs::write(
+            workflows.join(file),
+            "name: CI\non: push\njobs:\n  test:\n    runs-on: ubuntu-latest\n    steps:\n      - run: echo ci\n",
+        )
+        .unwrap();
+    }
+
+    let mut cmd = Command::cargo_bin("actionoscope").unwrap();
+    cmd.arg("ls").arg("--repo").arg(root).arg("-w").arg("CI");
+    cmd.assert()
+        .failure()
+        .code(11)
+        .stdout(predicate::str::contains(
+            "Workflow name 'CI' is used by several files",
+        ))
+        .stdout(predicate::str::contains("ci-macos.yml"));
+}
+
+#[test]
+fn test_select_by_name_reports_unparsable_workflows() {
+    let scratch = scratch_repository();
+    let root = scratch.path();
+    let workflows = root.join(".github").join("workflows");
+    fs::write(
+        workflows.join("broken.yml"),
+        "name: Broken\non: push\njobs:\n  test:\n    steps: 3\n",
+    )
+    .unwrap();
+
+    assert!(matches!(
+        repository::select_workflows(root, &[String::from("Broken")]),
+        Err(actionoscope::Error::Parse(_))
+    ));
+    let mut cmd = Command::cargo_bin("actionoscope").unwrap();
+    cmd.arg("ls")
+        .arg("--repo")
+        .arg(root)
+        .arg("-w")
+        .arg("Broken");
+    cmd.assert()
+        .code(3)
+        .stdout(predicate::str::contains("broken.yml"))
+        .stdout(predicate::str::contains("was not found").not());
+
+    // the other workflows are still found, with a warning about the broken one
+    fs::write(
+        workflows.join("named.yml"),
+        "name: Named
+on: push
+jobs:
+  test:
+    runs-on: ubuntu-latest
+    steps:
+      - run: pwd
+",
+    )
+    .unwrap();
+    let mut cmd = Command::cargo_bin("actionoscope").unwrap();
+    cmd.arg("ls").arg("--repo").arg(root).arg("-w").arg("Named");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Skipped a workflow that could not be parsed",
+        ))
+        .stdout(predicate::str::contains("broken.yml"));
+}
+
+#[test]
+fn test_github_context_comes_from_git() {
+    let scratch = scratch_repository();
+    let root = scratch.path();
+    fs::remove_dir_all(root.join(".git")).unwrap();
+    let git = |args: &[&str]| {
+        let output = std::process::Command::new("git")
+            .arg("-C")
+            .arg(root)
+            .args(["-c", "user.name=a", "-c", "user.email=a@example.com"])
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {args:?}");
+        String::from_utf8(output.stdout).unwrap().trim().to_string()
+    };
+    git(&["init", "-q", "-b", "release"]);
+    git(&[
+        "remote",
+        "add",
+        "origin",
+        "git@github.com:octo-org/hello.git",
+    ]);
+    git(&["commit", "-q", "--allow-empty", "-m", "first"]);
+    let sha = git(&["rev-parse", "HEAD"]);
+
+    let github = repository::github_context(root);
+    assert_eq!(github["sha"], sha);
+    assert_eq!(github["ref"], "refs/heads/release");
+    assert_eq!(github["ref_name"], "release");
+    assert_eq!(github["repository"], "octo-org/hello");
+    assert_eq!(github["repository_owner"], "octo-org");
+
+    let workflow = root.join(".github").join("workflows").join("context.yml");
+    fs::write(
+        &workflow,
+        "on: push\njobs:\n  show:\n    runs-on: ubuntu-latest\n    steps:\n      - run: echo \"${{ github.repository }}@${{ github.ref_name }} on '${{ matrix.os }}' by '${{ github.actor }}'\"\n",
+    )
+    .unwrap();
+    let mut cmd = Command::cargo_bin("actionoscope").unwrap();
+    cmd.arg("run")
+        .arg("--repo")
+        .arg(root)
+        .arg("-w")
+        .arg(&workflow);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "[cmd]: octo-org/hello@release on '' by ''",
+        ))
+        .stdout(predicate::str::contains(
+            "The `matrix` context is not available locally",
+        ))
+        .stdout(predicate::str::contains(
+            "`github.actor` is not known locally",
+        ));
+}
@@ -0,0 +1,106 @@
+use actionoscope::{schema, Action, Workflow};
+
+fn is_valid(yaml_data: &str) -> bool {
+    let instance: serde_json::Value =
+        serde_yaml::from_str(yaml_data).expect("Failed to parse YAML");
+    jsonschema::validator_for(&schema::workflow())
+        .expect("Invalid schema")
+        .is_valid(&instance)
+}
+
+#[test]
+fn test_schema_accepts_the_repository_workflow() {
+    let yaml_data = std::fs::read_to_string(".github/workflows/on.pr.yaml").unwrap();
+    Workflow::from_yaml(&yaml_data).expect("Failed to parse YAML");
+    assert!(is_valid(&yaml_data));
+}
+
+#[test]
+fn test_schema_accepts_every_form_of_shorthand_keys() {
+    let yaml_data = r#"
+on: [push, workflow_dispatch]
+permissions: read-all
+env:
+  RETRIES: 3
+  VERBOSE: true
+  EMPTY:
+jobs:
+  build:
+    runs-on: { group: large, labels: linux }
+    container: node:20
+    services:
+      db:
+        image: postgres:15
+        ports: [5432]
+    steps:
+      - uses: actions/checkout@v4
+  test:
+    needs: build
+    runs-on: [self-hosted, linux]
+    permissions:
+      contents: read
+      id-token: write
+    steps:
+      - uses: ./.github/actions/setup
+      - run: cargo test
+"#;
+    Workflow::from_yaml(yaml_data).expect("Failed to parse YAML");
+    assert!(is_valid(yaml_data));
+}
+
+#[test]
+fn test_schema_rejects_what_the_parser_rejects() {
+    let cases = [
+        "on: pushed\njobs: {}\n",
+        "on:\n  schedule:\n    - {}\njobs: {}\n",
+        "on: push\npermissions: read\njobs: {}\n",
+        "on: push\npermissions:\n  contents: admin\njobs: {}\n",
+        "on: push\njobs:\n  a:\n    runs-on: ubuntu-latest\n    steps:\n      - uses: actions/checkout\n",
+        "on: push\njobs:\n  a:\n    steps: []\n",
+    ];
+    for yaml_data in cases {
+        assert!(Workflow::from_yaml(yaml_data).is_err(), "{yaml_data}");
+        assert!(!is_valid(yaml_data), "{yaml_data}");
+    }
+}
+
+#[test]
+fn test_action_schema_rejects_what_the_parser_rejects() {
+    let is_valid_action = |yaml_data: &str| {
+        let instance: serde_json::Value =
+            serde_yaml::from_str(yaml_data).expect("Failed to parse YAML");
+        jsonschema::is_valid(&schema::action(), &instance)
+    };
+    let valid = [
+        "name: a\nruns:\n  using: node20\n  main: index.js\n",
+        "name: a\nruns:\n  using: docker\n  image: Dockerfile\n",
+        "name: a\nruns:\n  using: composite\n  steps:\n    - run: echo\n      shell: bash\n",
+    ];
+    for yaml_data in valid {
+        Action::from_yaml(yaml_data).expect("Failed to parse YAML");
+        assert!(is_valid_action(yaml_data), "{yaml_data}");
+    }
+    let invalid = [
+        "name: a\nruns:\n  using: node20\n",
+        "name: a\nruns:\n  using: node20\n  image: Dockerfile\n",
+        "name: a\nruns:\n  using: docker\n  main: index.js\n",
+        "name: a\nruns:\n  using: composite\n",
+        "name: a\nruns:\n  using: python\n  main: a.py\n",
+    ];
+    for yaml_data in invalid {
+        assert!(Action::from_yaml(yaml_data).is_err(), "{yaml_data}");
+        assert!(!is_valid_action(yaml_data), "{yaml_data}");
+    }
+}
+
+#[test]
+fn test_schema_command_prints_the_schema() {
+    let output = assert_cmd::Command::cargo_bin("actionoscope")
+        .unwrap()
+        .arg("schema")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let printed: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(printed, schema::workflow());
+}
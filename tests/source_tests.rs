@@ -0,0 +1,86 @@
+mod common;
+
+use actionoscope::Workflow;
+
+const WORKFLOW: &str = r#"name: Test Workflow
+on: push
+jobs:
+  build:
+    runs-on: ubuntu-latest
+    steps:
+      - name: Checkout
+        uses: actions/checkout@v4
+      - name: Build ✓
+        id: build
+        run: cargo build
+  test:
+    runs-on: ubuntu-latest
+    steps:
+      - run: cargo test
+"#;
+
+#[test]
+fn test_job_and_step_locations() {
+    let workflow = Workflow::from_yaml(WORKFLOW).expect("Failed to parse YAML");
+    let build = workflow.get_job("build").unwrap();
+    assert_eq!(build.location().unwrap().to_string(), "4:3");
+    assert_eq!(build.steps[1].location().unwrap().to_string(), "9:9");
+    let test = workflow.get_job("test").unwrap();
+    assert_eq!(test.location().unwrap().to_string(), "12:3");
+    assert_eq!(test.steps[0].location().unwrap().to_string(), "15:9");
+}
+
+#[test]
+fn test_key_and_value_spans() {
+    let workflow = Workflow::from_yaml(WORKFLOW).expect("Failed to parse YAML");
+    let source_map = &workflow.source_map;
+    let key = source_map.key("jobs.build.steps[1].run").unwrap();
+    assert_eq!((key.start.line, key.start.column), (11, 9));
+    let value = source_map.value("jobs.build.steps[1].name").unwrap();
+    assert_eq!(&WORKFLOW[value.start.offset..value.end.offset], "Build ✓");
+    let value = source_map.value("jobs.build.steps[1].run").unwrap();
+    assert_eq!(
+        &WORKFLOW[value.start.offset..value.end.offset],
+        "cargo build"
+    );
+    assert!(source_map.value("jobs.build.steps[2]").is_none());
+}
+
+#[test]
+fn test_locations_include_file() {
+    let scratch = common::scratch_dir();
+    let path = scratch.path().join("source_ci.yml");
+    std::fs::write(&path, WORKFLOW).unwrap();
+    let workflow = Workflow::from_file(&path).expect("Failed to load workflow");
+    let step = &workflow.get_job("test").unwrap().steps[0];
+    assert_eq!(
+        step.location().unwrap().to_string(),
+        format!("{}:15:9", path.display())
+    );
+}
+
+#[test]
+fn test_parse_error_location() {
+    let yaml_data = r#"name: Test Workflow
+on: push
+jobs:
+  build:
+    steps:
+      - run: cargo build
+"#;
+    let scratch = common::scratch_dir();
+    let path = scratch.path().join("source_broken.yml");
+    std::fs::write(&path, yaml_data).unwrap();
+    let err = Workflow::from_file(&path).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "{}:5:5: jobs.build: missing field `runs-on`",
+            path.display()
+        )
+    );
+
+    let err = Workflow::from_yaml(yaml_data).unwrap_err();
+    let location = err.location.expect("error has no location");
+    assert_eq!((location.line, location.column), (5, 5));
+}
@@ -1,4 +1,9 @@
-use actionoscope::Step;
+mod common;
+
+use actionoscope::{Error, Minutes, RunDefaults, Step, StepContext, Workflow};
+use std::collections::HashMap;
+use std::sync::atomic::Ordering;
+use std::time::Instant;
 
 #[test]
 fn test_get_name_or_id_with_name() {
@@ -9,6 +14,7 @@ fn test_get_name_or_id_with_name() {
         shell: None,
         working_directory: None,
         run: None,
+        ..Default::default()
     };
     assert_eq!(step.get_name_or_id(), "Test Step");
 }
@@ -22,6 +28,7 @@ fn test_get_name_or_id_with_id() {
         shell: None,
         working_directory: None,
         run: None,
+        ..Default::default()
     };
     assert_eq!(step.get_name_or_id(), "test_step");
 }
@@ -35,6 +42,7 @@ fn test_get_name_or_id_with_name_and_id() {
         shell: None,
         working_directory: None,
         run: None,
+        ..Default::default()
     };
     assert_eq!(step.get_name_or_id(), "Test Step");
 }
@@ -48,6 +56,7 @@ fn test_get_name_or_id_with_none() {
         shell: None,
         working_directory: None,
         run: None,
+        ..Default::default()
     };
     assert_eq!(step.get_name_or_id(), "unknown");
 }
@@ -61,6 +70,7 @@ fn test_run_cmd_with_valid_command() {
         shell: Some(String::from("echo")),
         working_directory: None,
         run: Some(String::from("Hello, world!")),
+        ..Default::default()
     };
     assert!(step.run_cmd(None, None).is_ok());
 }
@@ -74,6 +84,7 @@ fn test_run_cmd_with_invalid_command() {
         shell: Some(String::from("invalid_shell")),
         working_directory: None,
         run: Some(String::from("Hello, world!")),
+        ..Default::default()
     };
     assert!(step.run_cmd(None, None).is_err());
 }
@@ -87,6 +98,205 @@ fn test_run_cmd_with_no_run_command() {
         shell: Some(String::from("echo")),
         working_directory: None,
         run: None,
+        ..Default::default()
     };
     assert!(step.run_cmd(None, None).is_err());
 }
+
+#[test]
+fn test_run_in_uses_default_working_directory() {
+    let scratch = common::scratch_dir();
+    let dir = scratch.path().join("actionoscope_defaults_wd");
+    std::fs::create_dir_all(&dir).unwrap();
+    let context = StepContext {
+        defaults: RunDefaults {
+            shell: Some(String::from("bash")),
+            working_directory: Some(dir.to_string_lossy().into_owned()),
+        },
+        ..Default::default()
+    };
+    let step = Step {
+        name: Some(String::from("Test Step")),
+        run: Some(String::from(
+            r#"test "$(basename "$PWD")" = actionoscope_defaults_wd"#,
+        )),
+        ..Default::default()
+    };
+    assert!(step.run_in(&context).is_ok());
+}
+
+#[test]
+fn test_run_in_step_shell_overrides_default() {
+    let context = StepContext {
+        defaults: RunDefaults {
+            shell: Some(String::from("invalid_shell")),
+            working_directory: None,
+        },
+        ..Default::default()
+    };
+    let step = Step {
+        name: Some(String::from("Test Step")),
+        run: Some(String::from("true")),
+        ..Default::default()
+    };
+    assert!(step.run_in(&context).is_err());
+
+    let step = Step {
+        shell: Some(String::from("bash")),
+        ..step
+    };
+    assert!(step.run_in(&context).is_ok());
+}
+
+#[test]
+fn test_run_in_exports_env_with_step_precedence() {
+    let context = StepContext {
+        env_vars: Some(std::collections::HashMap::from([
+            (String::from("STAGE"), String::from("job")),
+            (String::from("REGION"), String::from("eu-west-1")),
+        ])),
+        ..Default::default()
+    };
+    let step: Step = serde_yaml::from_str(
+        r#"
+        name: Test Step
+        env:
+          STAGE: step
+          TARGET: ${{ env.REGION }}
+        run: test "$STAGE" = step && test "$TARGET" = eu-west-1 && test "$REGION" = eu-west-1
+        "#,
+    )
+    .unwrap();
+    assert!(step.run_in(&context).is_ok());
+}
+
+#[test]
+fn test_inputs_and_input_env() {
+    let context = StepContext {
+        env_vars: Some(std::collections::HashMap::from([(
+            String::from("TOOLCHAIN"),
+            String::from("stable"),
+        )])),
+        secret_vars: Some(std::collections::HashMap::from([(
+            String::from("TOKEN"),
+            String::from("s3cr3t"),
+        )])),
+        ..Default::default()
+    };
+    let step: Step = serde_yaml::from_str(
+        r#"
+        uses: dtolnay/rust-toolchain@master
+        with:
+          toolchain: ${{ env.TOOLCHAIN }}
+          fetch-depth: 0
+          persist credentials: false
+          token: ${{ secrets.TOKEN }}
+        "#,
+    )
+    .unwrap();
+
+    let inputs = step.inputs(&context);
+    assert_eq!(inputs["toolchain"], "stable");
+    assert_eq!(inputs["fetch-depth"], "0");
+
+    let input_env: Vec<(String, String)> = step.input_env(&context).into_iter().collect();
+    assert_eq!(
+        input_env,
+        vec![
+            (String::from("INPUT_TOOLCHAIN"), String::from("stable")),
+            (String::from("INPUT_FETCH-DEPTH"), String::from("0")),
+            (
+                String::from("INPUT_PERSIST_CREDENTIALS"),
+                String::from("false")
+            ),
+            (String::from("INPUT_TOKEN"), String::from("s3cr3t")),
+        ]
+    );
+}
+
+#[test]
+fn test_run_cmd_reports_exit_code() {
+    let step = Step {
+        name: Some(String::from("Test Step")),
+        run: Some(String::from("exit 3")),
+        ..Default::default()
+    };
+    match step.run_cmd(None, None) {
+        Err(Error::Exit { step, code, .. }) => {
+            assert_eq!(step, "Test Step");
+            assert_eq!(code, Some(3));
+        }
+        other => panic!("expected an exit error, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_run_in_kills_step_after_timeout() {
+    let step = Step {
+        name: Some(String::from("Slow Step")),
+        run: Some(String::from("sleep 30")),
+        timeout_minutes: Some(Minutes::Number(0.005)),
+        ..Default::default()
+    };
+    let started = Instant::now();
+    let result = step.run_in(&StepContext::default());
+    assert!(matches!(result, Err(Error::Timeout { .. })));
+    assert!(started.elapsed().as_secs() < 10);
+}
+
+#[test]
+fn test_run_in_cancelled() {
+    let context = StepContext::default();
+    context.cancelled.store(true, Ordering::SeqCst);
+    let step = Step {
+        name: Some(String::from("Slow Step")),
+        run: Some(String::from("sleep 30")),
+        ..Default::default()
+    };
+    assert!(matches!(
+        step.run_in(&context),
+        Err(Error::Cancelled { .. })
+    ));
+}
+
+#[test]
+fn test_timeout_minutes_may_be_an_expression() {
+    let workflow = Workflow::from_yaml(
+        r#"
+on:
+  workflow_dispatch:
+    inputs:
+      timeout:
+        type: number
+        default: 5
+jobs:
+  a:
+    runs-on: ubuntu-latest
+    timeout-minutes: ${{ inputs.timeout }}
+    steps:
+      - run: cargo test
+        timeout-minutes: ${{ inputs.timeout }}
+"#,
+    )
+    .unwrap();
+    let job = workflow.get_job("a").unwrap();
+    assert_eq!(
+        job.timeout_minutes,
+        Some(Minutes::Expression(String::from("${{ inputs.timeout }}")))
+    );
+    assert!(workflow
+        .to_yaml()
+        .contains("timeout-minutes: ${{ inputs.timeout }}"));
+
+    let inputs = workflow.resolve_inputs(&HashMap::new()).unwrap();
+    let context = workflow.step_context_with_inputs(job, None, inputs);
+    assert_eq!(context.deadline.map(|deadline| deadline.minutes), Some(5.0));
+
+    // an expression that cannot be evaluated sets no deadline
+    let step = Step {
+        run: Some(String::from("true")),
+        timeout_minutes: Some(Minutes::from("${{ inputs.timeout == }}")),
+        ..Default::default()
+    };
+    assert!(step.run_in(&StepContext::default()).is_ok());
+}
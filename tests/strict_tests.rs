@@ -0,0 +1,100 @@
+use actionoscope::{ParseOptions, Workflow};
+
+const STRICT: ParseOptions = ParseOptions { strict: true };
+
+const WORKFLOW_WITH_TYPOS: &str = r#"name: Test Workflow
+on:
+  push:
+    branch: [main]
+jobs:
+  build:
+    run_on: ubuntu-latest
+    steps:
+      - name: Build
+        working_directory: src
+        run: cargo build
+"#;
+
+#[test]
+fn test_lenient_mode_ignores_unknown_keys() {
+    let yaml_data = r#"
+    name: Test Workflow
+    on: push
+    jobs:
+      build:
+        runs-on: ubuntu-latest
+        steps:
+          - name: Build
+            working_directory: src
+            run: cargo build
+    "#;
+    let workflow = Workflow::from_yaml(yaml_data).expect("Failed to parse YAML");
+    assert!(workflow.get_job("build").unwrap().steps[0]
+        .working_directory
+        .is_none());
+    assert!(Workflow::from_yaml_with_options(yaml_data, &STRICT).is_err());
+}
+
+#[test]
+fn test_strict_mode_reports_every_unknown_key() {
+    let err = Workflow::from_yaml_with_options(WORKFLOW_WITH_TYPOS, &STRICT).unwrap_err();
+    let reported: Vec<(String, usize, usize, Option<String>)> = err
+        .unknown_keys
+        .iter()
+        .map(|key| {
+            let location = key.location.clone().unwrap();
+            (
+                key.key.clone(),
+                location.line,
+                location.column,
+                key.suggestion.clone(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        reported,
+        vec![
+            ("branch".to_string(), 4, 5, Some("branches".to_string())),
+            ("run_on".to_string(), 7, 5, Some("runs-on".to_string())),
+            (
+                "working_directory".to_string(),
+                10,
+                9,
+                Some("working-directory".to_string())
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_strict_mode_error_message() {
+    let err = Workflow::from_yaml_with_options(WORKFLOW_WITH_TYPOS, &STRICT).unwrap_err();
+    let message = err.to_string();
+    assert!(message.contains(
+        "10:9: unknown key `working_directory` in jobs.build.steps[0], did you mean `working-directory`?"
+    ));
+    assert_eq!(message.lines().count(), 3);
+}
+
+#[test]
+fn test_strict_mode_without_suggestion() {
+    let yaml_data = r#"
+    on: push
+    jobs:
+      build:
+        runs-on: ubuntu-latest
+        flavour: vanilla
+        steps:
+          - run: cargo build
+    "#;
+    let err = Workflow::from_yaml_with_options(yaml_data, &STRICT).unwrap_err();
+    assert_eq!(err.unknown_keys.len(), 1);
+    assert_eq!(err.unknown_keys[0].key, "flavour");
+    assert!(err.unknown_keys[0].suggestion.is_none());
+}
+
+#[test]
+fn test_strict_mode_accepts_valid_workflow() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/.github/workflows/on.pr.yaml");
+    Workflow::from_file_with_options(path, &STRICT).expect("Failed to load workflow");
+}
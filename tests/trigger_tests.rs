@@ -0,0 +1,101 @@
+use actionoscope::Workflow;
+
+fn workflow_with_on(on: &str) -> Workflow {
+    let yaml_data = format!(
+        r#"
+name: Test Workflow
+on: {on}
+jobs:
+  test_job:
+    runs-on: ubuntu-latest
+    steps:
+      - run: echo "Hello, world!"
+"#
+    );
+    Workflow::from_yaml(&yaml_data).expect("Failed to parse YAML")
+}
+
+#[test]
+fn test_on_single_event() {
+    let workflow = workflow_with_on("push");
+    assert!(workflow.on.push.is_some());
+    assert!(workflow.on.pull_request.is_none());
+}
+
+#[test]
+fn test_on_list_of_events() {
+    let workflow = workflow_with_on("[push, pull_request, workflow_dispatch]");
+    assert_eq!(
+        workflow.on.event_names(),
+        vec!["push", "pull_request", "workflow_dispatch"]
+    );
+}
+
+#[test]
+fn test_on_map_with_filters() {
+    let workflow = workflow_with_on(
+        r#"
+  push:
+    branches-ignore: [ "release/**" ]
+    tags: v*
+    paths-ignore:
+      - docs/**
+  pull_request_target:
+    types: [ opened, synchronize ]
+  workflow_dispatch:
+  release:
+    types: published
+  merge_group: {}
+"#,
+    );
+    let push = workflow.on.push.as_ref().expect("push not parsed");
+    assert_eq!(push.branches_ignore, Some(vec!["release/**".to_string()]));
+    assert_eq!(push.tags, Some(vec!["v*".to_string()]));
+    assert_eq!(push.paths_ignore, Some(vec!["docs/**".to_string()]));
+    let pull_request_target = workflow.on.pull_request_target.as_ref().unwrap();
+    assert_eq!(
+        pull_request_target.types,
+        Some(vec!["opened".to_string(), "synchronize".to_string()])
+    );
+    assert!(workflow.on.workflow_dispatch.is_some());
+    assert_eq!(
+        workflow.on.release.as_ref().unwrap().types,
+        Some(vec!["published".to_string()])
+    );
+    assert!(workflow.on.merge_group.is_some());
+}
+
+#[test]
+fn test_on_schedule_and_workflow_run() {
+    let workflow = workflow_with_on(
+        r#"
+  schedule:
+    - cron: "0 3 * * *"
+    - cron: "30 12 * * 1"
+  workflow_run:
+    workflows: [ CI ]
+    types: [ completed ]
+    branches: [ main ]
+"#,
+    );
+    let schedule = workflow.on.schedule.as_ref().unwrap();
+    assert_eq!(schedule.len(), 2);
+    assert_eq!(schedule[1].cron, "30 12 * * 1");
+    let workflow_run = workflow.on.workflow_run.as_ref().unwrap();
+    assert_eq!(workflow_run.workflows, Some(vec!["CI".to_string()]));
+}
+
+#[test]
+fn test_on_unknown_event() {
+    let yaml_data = r#"
+name: Test Workflow
+on: [push, pusj]
+jobs:
+  test_job:
+    runs-on: ubuntu-latest
+    steps:
+      - run: echo "Hello, world!"
+"#;
+    let err = Workflow::from_yaml(yaml_data).unwrap_err();
+    assert!(err.to_string().contains("unknown field `pusj`"));
+}
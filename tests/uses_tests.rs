@@ -0,0 +1,120 @@
+use actionoscope::{Uses, Workflow};
+
+#[test]
+fn test_parse_local_action() {
+    let uses: Uses = "./.github/actions/prepare-environment".parse().unwrap();
+    assert_eq!(
+        uses,
+        Uses::Local {
+            path: "./.github/actions/prepare-environment".to_string()
+        }
+    );
+    assert!(!uses.is_workflow());
+    assert_eq!(uses.git_ref(), None);
+}
+
+#[test]
+fn test_parse_repository_actions() {
+    let uses: Uses = "actions/checkout@v4".parse().unwrap();
+    assert_eq!(
+        uses,
+        Uses::Repository {
+            owner: "actions".to_string(),
+            repo: "checkout".to_string(),
+            path: None,
+            git_ref: "v4".to_string(),
+        }
+    );
+    assert!(!uses.is_pinned_to_sha());
+
+    let uses: Uses = "github/codeql-action/init@8fd294e26a0e458834582b0fe4988d79966c7c0a"
+        .parse()
+        .unwrap();
+    assert_eq!(
+        uses,
+        Uses::Repository {
+            owner: "github".to_string(),
+            repo: "codeql-action".to_string(),
+            path: Some("init".to_string()),
+            git_ref: "8fd294e26a0e458834582b0fe4988d79966c7c0a".to_string(),
+        }
+    );
+    assert!(uses.is_pinned_to_sha());
+
+    let uses: Uses = "owner/repo@feature/new-thing".parse().unwrap();
+    assert_eq!(uses.git_ref(), Some("feature/new-thing"));
+}
+
+#[test]
+fn test_parse_reusable_workflow_and_docker() {
+    let uses: Uses = "octo-org/ci/.github/workflows/build.yml@main"
+        .parse()
+        .unwrap();
+    assert_eq!(
+        uses,
+        Uses::ReusableWorkflow {
+            owner: "octo-org".to_string(),
+            repo: "ci".to_string(),
+            path: ".github/workflows/build.yml".to_string(),
+            git_ref: "main".to_string(),
+        }
+    );
+    assert!(uses.is_workflow());
+
+    let uses: Uses = "docker://alpine:3.19".parse().unwrap();
+    assert_eq!(
+        uses,
+        Uses::Docker {
+            image: "alpine:3.19".to_string()
+        }
+    );
+}
+
+#[test]
+fn test_parse_invalid_references() {
+    for invalid in [
+        "",
+        "actions/checkout",
+        "actions/checkout@",
+        "checkout@v4",
+        "/abs/path",
+        "../sibling",
+        "owner/repo//path@v1",
+        "docker://",
+    ] {
+        assert!(invalid.parse::<Uses>().is_err(), "{invalid:?} was accepted");
+    }
+    let err = "actions/checkout".parse::<Uses>().unwrap_err();
+    assert!(err
+        .to_string()
+        .starts_with("invalid uses `actions/checkout`"));
+}
+
+#[test]
+fn test_display_round_trips() {
+    for value in [
+        "./.github/actions/prepare-environment",
+        "actions/checkout@v4",
+        "github/codeql-action/init@v3",
+        "octo-org/ci/.github/workflows/build.yml@main",
+        "docker://ghcr.io/owner/image:latest",
+    ] {
+        assert_eq!(value.parse::<Uses>().unwrap().to_string(), value);
+    }
+}
+
+#[test]
+fn test_invalid_uses_in_workflow() {
+    let yaml_data = r#"
+on: push
+jobs:
+  build:
+    runs-on: ubuntu-latest
+    steps:
+      - uses: actions/checkout
+"#;
+    let err = Workflow::from_yaml(yaml_data).unwrap_err();
+    let location = err.location.clone().unwrap();
+    assert_eq!(location.line, 7);
+    assert!(err.message.contains("invalid uses `actions/checkout`"));
+}
@@ -1,4 +1,6 @@
-use actionoscope::Workflow;
+mod common;
+
+use actionoscope::{RunDefaults, RunsOn, Workflow};
 
 #[test]
 fn test_workflow_from_yaml() {
@@ -17,7 +19,7 @@ fn test_workflow_from_yaml() {
     "#;
 
     let workflow = Workflow::from_yaml(yaml_data).expect("Failed to parse YAML");
-    assert_eq!(workflow.name, "Test Workflow");
+    assert_eq!(workflow.name.as_deref(), Some("Test Workflow"));
     assert!(workflow.get_job("test_job").is_some());
 }
 
@@ -39,7 +41,10 @@ fn test_get_job() {
 
     let workflow = Workflow::from_yaml(yaml_data).expect("Failed to parse YAML");
     let job = workflow.get_job("test_job").expect("Job not found");
-    assert_eq!(job.runs_on, "ubuntu-latest");
+    assert_eq!(
+        job.runs_on,
+        RunsOn::Labels(vec!["ubuntu-latest".to_string()])
+    );
 }
 
 #[test]
@@ -129,3 +134,252 @@ fn test_get_all_steps_since_and_until() {
     assert_eq!(steps[0].get_name_or_id(), "Step 2");
     assert_eq!(steps[1].get_name_or_id(), "Step 3");
 }
+
+#[test]
+fn test_runs_on_forms() {
+    let yaml_data = r#"
+    on: push
+    jobs:
+      labels:
+        runs-on: [self-hosted, linux]
+        steps:
+          - run: echo "labels"
+      group:
+        runs-on:
+          group: ubuntu-runners
+          labels: ubuntu-20.04-16core
+        steps:
+          - run: echo "group"
+      expression:
+        runs-on: ${{ matrix.os }}
+        steps:
+          - run: echo "expression"
+    "#;
+
+    let workflow = Workflow::from_yaml(yaml_data).expect("Failed to parse YAML");
+    assert_eq!(
+        workflow.get_job("labels").unwrap().runs_on,
+        RunsOn::Labels(vec!["self-hosted".to_string(), "linux".to_string()])
+    );
+    assert_eq!(
+        workflow.get_job("group").unwrap().runs_on,
+        RunsOn::Group {
+            group: Some("ubuntu-runners".to_string()),
+            labels: vec!["ubuntu-20.04-16core".to_string()],
+        }
+    );
+    assert_eq!(
+        workflow.get_job("expression").unwrap().runs_on,
+        RunsOn::Expression("${{ matrix.os }}".to_string())
+    );
+}
+
+#[test]
+fn test_display_name_defaults_to_file_path() {
+    let yaml_data = r#"
+    on: push
+    jobs:
+      test_job:
+        runs-on: ubuntu-latest
+        steps:
+          - run: echo "Hello, world!"
+    "#;
+
+    let repository = common::scratch_repository();
+    let workflows = repository
+        .path()
+        .join(".github")
+        .join("workflows")
+        .join("nested");
+    std::fs::create_dir_all(&workflows).unwrap();
+    let path = workflows.join("ci.yml");
+    std::fs::write(&path, yaml_data).unwrap();
+    let workflow = Workflow::from_file(&path).expect("Failed to load workflow");
+    assert!(workflow.name.is_none());
+    assert_eq!(
+        workflow.display_name(),
+        std::path::Path::new(".github/workflows/nested/ci.yml").to_string_lossy()
+    );
+
+    // outside a repository the path is shown as given
+    let scratch = common::scratch_dir();
+    let outside = scratch.path().join("unnamed_workflow.yml");
+    std::fs::write(&outside, yaml_data).unwrap();
+    let workflow = Workflow::from_file(&outside).expect("Failed to load workflow");
+    assert_eq!(workflow.display_name(), outside.to_string_lossy());
+}
+
+#[test]
+fn test_jobs_keep_declaration_order() {
+    let yaml_data = r#"
+    on: push
+    jobs:
+      zeta:
+        runs-on: ubuntu-latest
+        steps:
+          - run: echo "zeta"
+      alpha:
+        runs-on: ubuntu-latest
+        steps:
+          - run: echo "alpha"
+      mid:
+        runs-on: ubuntu-latest
+        steps:
+          - run: echo "mid"
+    "#;
+
+    let workflow = Workflow::from_yaml(yaml_data).expect("Failed to parse YAML");
+    let names: Vec<&String> = workflow.jobs.keys().collect();
+    assert_eq!(names, vec!["zeta", "alpha", "mid"]);
+}
+
+#[test]
+fn test_jobs_in_needs_order() {
+    let yaml_data = r#"
+    on: push
+    jobs:
+      deploy:
+        needs: [build, test]
+        runs-on: ubuntu-latest
+        steps:
+          - run: echo "deploy"
+      test:
+        needs: build
+        runs-on: ubuntu-latest
+        steps:
+          - run: echo "test"
+      lint:
+        runs-on: ubuntu-latest
+        steps:
+          - run: echo "lint"
+      build:
+        runs-on: ubuntu-latest
+        steps:
+          - run: echo "build"
+    "#;
+
+    let workflow = Workflow::from_yaml(yaml_data).expect("Failed to parse YAML");
+    assert_eq!(workflow.get_job("test").unwrap().needs(), ["build"]);
+    let order: Vec<&String> = workflow
+        .jobs_in_order()
+        .expect("Failed to order jobs")
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect();
+    assert_eq!(order, vec!["lint", "build", "test", "deploy"]);
+}
+
+#[test]
+fn test_jobs_in_order_with_cycle() {
+    let yaml_data = r#"
+    on: push
+    jobs:
+      a:
+        needs: c
+        runs-on: ubuntu-latest
+        steps:
+          - run: echo "a"
+      b:
+        needs: a
+        runs-on: ubuntu-latest
+        steps:
+          - run: echo "b"
+      c:
+        needs: b
+        runs-on: ubuntu-latest
+        steps:
+          - run: echo "c"
+    "#;
+
+    let workflow = Workflow::from_yaml(yaml_data).expect("Failed to parse YAML");
+    let err = workflow.jobs_in_order().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Jobs form a `needs` cycle: a -> c -> b -> a"
+    );
+}
+
+#[test]
+fn test_jobs_in_order_with_missing_job() {
+    let yaml_data = r#"
+    on: push
+    jobs:
+      test:
+        needs: build
+        runs-on: ubuntu-latest
+        steps:
+          - run: echo "test"
+    "#;
+
+    let workflow = Workflow::from_yaml(yaml_data).expect("Failed to parse YAML");
+    let err = workflow.jobs_in_order().unwrap_err();
+    assert!(err.to_string().contains("needs job 'build'"));
+}
+
+#[test]
+fn test_step_context_defaults_precedence() {
+    let yaml_data = r#"
+    on: push
+    defaults:
+      run:
+        shell: sh
+        working-directory: workflow-dir
+    jobs:
+      inherits:
+        runs-on: ubuntu-latest
+        steps:
+          - run: echo "inherits"
+      overrides:
+        runs-on: ubuntu-latest
+        defaults:
+          run:
+            working-directory: job-dir
+        steps:
+          - run: echo "overrides"
+    "#;
+
+    let workflow = Workflow::from_yaml(yaml_data).expect("Failed to parse YAML");
+    let context = workflow.step_context(workflow.get_job("inherits").unwrap(), None);
+    assert_eq!(
+        context.defaults,
+        RunDefaults {
+            shell: Some("sh".to_string()),
+            working_directory: Some("workflow-dir".to_string()),
+        }
+    );
+    let context = workflow.step_context(workflow.get_job("overrides").unwrap(), None);
+    assert_eq!(
+        context.defaults,
+        RunDefaults {
+            shell: Some("sh".to_string()),
+            working_directory: Some("job-dir".to_string()),
+        }
+    );
+}
+
+#[test]
+fn test_step_context_env_precedence() {
+    let yaml_data = r#"
+    on: push
+    env:
+      STAGE: workflow
+      REGION: eu-west-1
+      PORT: 8080
+    jobs:
+      deploy:
+        runs-on: ubuntu-latest
+        env:
+          STAGE: job
+          TARGET: ${{ env.REGION }}-${{ secrets.ACCOUNT }}
+        steps:
+          - run: echo "deploy"
+    "#;
+
+    let workflow = Workflow::from_yaml(yaml_data).expect("Failed to parse YAML");
+    let secrets = std::collections::HashMap::from([("ACCOUNT".to_string(), "42".to_string())]);
+    let context = workflow.step_context(workflow.get_job("deploy").unwrap(), Some(secrets));
+    let env_vars = context.env_vars.expect("env not merged");
+    assert_eq!(env_vars["STAGE"], "job");
+    assert_eq!(env_vars["PORT"], "8080");
+    assert_eq!(env_vars["TARGET"], "eu-west-1-42");
+}